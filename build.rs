@@ -49,6 +49,7 @@ fn main() {
             .entry("anyOf")
             .entry("items")
             .entry("oneOf")
+            .entry("prefixItems")
             .build()
     )
     .unwrap();
@@ -58,7 +59,9 @@ fn main() {
         &mut file,
         "static FINAL_KEYS: phf::Set<&'static str> = {};",
         phf_codegen::Set::new()
+            .entry("$vocabulary")
             .entry("default")
+            .entry("discriminator")
             .entry("enum")
             .entry("required")
             .entry("type")
@@ -76,11 +79,19 @@ fn main() {
             .entry("$schema")
             .entry("$id")
             .entry("$anchor")
+            .entry("$recursiveAnchor")
+            .entry("$dynamicAnchor")
+            .entry("$vocabulary")
+            .entry("$comment")
             .entry("default")
             .entry("title")
             .entry("description")
             .entry("format")
             .entry("examples")
+            .entry("readOnly")
+            .entry("writeOnly")
+            .entry("deprecated")
+            .entry("discriminator")
             .build()
     )
     .unwrap();
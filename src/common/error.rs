@@ -3,6 +3,14 @@ use std::fmt::Debug;
 
 use downcast_rs::Downcast;
 
+/// Where the schema keyword that produced an error is, see the accessors of `ValicoError`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ErrorLocation {
+    pub keyword_location: Option<String>,
+    pub schema_path: Option<String>,
+    pub schema_url: Option<String>,
+}
+
 pub trait ValicoError: Downcast + Error + Send + Debug + erased_serde::Serialize {
     fn get_code(&self) -> &str;
     fn get_path(&self) -> &str;
@@ -10,6 +18,23 @@ pub trait ValicoError: Downcast + Error + Send + Debug + erased_serde::Serialize
     fn get_detail(&self) -> Option<&str> {
         None
     }
+    /// The evaluation path of the schema keyword that produced the error, i.e. the JSON
+    /// pointer through the schema that includes the followed references. Only errors of
+    /// schema keywords have one, and only once they have been returned from validation.
+    fn get_keyword_location(&self) -> Option<&str> {
+        None
+    }
+    fn set_keyword_location(&mut self, _keyword_location: String) {}
+    /// The JSON pointer of the schema keyword that produced the error within its schema
+    /// document.
+    fn get_schema_path(&self) -> Option<&str> {
+        None
+    }
+    /// The absolute URL of the schema keyword that produced the error.
+    fn get_schema_url(&self) -> Option<&str> {
+        None
+    }
+    fn set_schema_location(&mut self, _schema_path: String, _schema_url: String) {}
 }
 
 erased_serde::serialize_trait_object!(ValicoError);
@@ -83,6 +108,73 @@ macro_rules! impl_err {
             }
         }
     };
+
+    ($err:ty, $code:expr, $title:expr, +location) => {
+        impl_basic_err!($err, $code);
+
+        impl $crate::common::error::ValicoError for $err {
+            fn get_code(&self) -> &str {
+                $code
+            }
+            fn get_title(&self) -> &str {
+                $title
+            }
+            fn get_path(&self) -> &str {
+                self.path.as_ref()
+            }
+            fn get_keyword_location(&self) -> Option<&str> {
+                self.location.keyword_location.as_deref()
+            }
+            fn set_keyword_location(&mut self, keyword_location: String) {
+                self.location.keyword_location = Some(keyword_location);
+            }
+            fn get_schema_path(&self) -> Option<&str> {
+                self.location.schema_path.as_deref()
+            }
+            fn get_schema_url(&self) -> Option<&str> {
+                self.location.schema_url.as_deref()
+            }
+            fn set_schema_location(&mut self, schema_path: String, schema_url: String) {
+                self.location.schema_path = Some(schema_path);
+                self.location.schema_url = Some(schema_url);
+            }
+        }
+    };
+
+    ($err:ty, $code:expr, $title:expr, +detail, +location) => {
+        impl_basic_err!($err, $code);
+
+        impl $crate::common::error::ValicoError for $err {
+            fn get_code(&self) -> &str {
+                $code
+            }
+            fn get_title(&self) -> &str {
+                $title
+            }
+            fn get_path(&self) -> &str {
+                self.path.as_ref()
+            }
+            fn get_detail(&self) -> Option<&str> {
+                Some(self.detail.as_ref())
+            }
+            fn get_keyword_location(&self) -> Option<&str> {
+                self.location.keyword_location.as_deref()
+            }
+            fn set_keyword_location(&mut self, keyword_location: String) {
+                self.location.keyword_location = Some(keyword_location);
+            }
+            fn get_schema_path(&self) -> Option<&str> {
+                self.location.schema_path.as_deref()
+            }
+            fn get_schema_url(&self) -> Option<&str> {
+                self.location.schema_url.as_deref()
+            }
+            fn set_schema_location(&mut self, schema_path: String, schema_url: String) {
+                self.location.schema_path = Some(schema_path);
+                self.location.schema_url = Some(schema_url);
+            }
+        }
+    };
 }
 
 macro_rules! impl_serialize {
@@ -96,6 +188,15 @@ macro_rules! impl_serialize {
                 if let Some(ref detail) = self.get_detail() {
                     map.insert("detail".to_string(), to_value(detail).unwrap());
                 }
+                if let Some(location) = self.get_keyword_location() {
+                    map.insert("keyword_location".to_string(), to_value(location).unwrap());
+                }
+                if let Some(path) = self.get_schema_path() {
+                    map.insert("schema_path".to_string(), to_value(path).unwrap());
+                }
+                if let Some(url) = self.get_schema_url() {
+                    map.insert("schema_url".to_string(), to_value(url).unwrap());
+                }
                 Value::Object(map).serialize(serializer)
             }
         }
@@ -110,6 +211,15 @@ macro_rules! impl_serialize {
                 if let Some(ref detail) = self.get_detail() {
                     map.insert("detail".to_string(), to_value(detail).unwrap());
                 }
+                if let Some(location) = self.get_keyword_location() {
+                    map.insert("keyword_location".to_string(), to_value(location).unwrap());
+                }
+                if let Some(path) = self.get_schema_path() {
+                    map.insert("schema_path".to_string(), to_value(path).unwrap());
+                }
+                if let Some(url) = self.get_schema_url() {
+                    map.insert("schema_url".to_string(), to_value(url).unwrap());
+                }
                 $({
                     let closure = $sp;
                     closure(self, &mut map);
@@ -77,9 +77,7 @@ impl<T> ExtendedResult<T> {
             value,
             state: json_schema::ValidationState {
                 errors,
-                missing: vec![],
-                replacement: None,
-                evaluated: Default::default(),
+                ..json_schema::ValidationState::new()
             },
         }
     }
@@ -0,0 +1,90 @@
+//! Selection of the most relevant error, see `ValidationState::best_error`.
+
+use super::super::common::error::ValicoError;
+use super::errors;
+use super::validators::ValidationState;
+
+/// The codes of the errors that tell that a branch was not meant for a value when they
+/// concern a property of it, as with the tag of a discriminated union.
+const DISCRIMINATING_CODES: [&str; 2] = ["const", "enum"];
+
+/// Picks the error with the deepest instance path, where failed `anyOf` and `oneOf`
+/// are replaced by the best error of their most relevant branch.
+pub(crate) fn best_error(errors: &[Box<dyn ValicoError>]) -> Option<&dyn ValicoError> {
+    let mut best: Option<&dyn ValicoError> = None;
+    for error in errors.iter() {
+        let error = innermost(error.as_ref());
+        match best {
+            Some(best) if depth(best.get_path()) >= depth(error.get_path()) => {}
+            _ => best = Some(error),
+        }
+    }
+    best
+}
+
+/// Picks the most relevant of the failed branches of an applicator at `path`.
+///
+/// A branch whose `type` holds for the value, and `const` and `enum` for the value and
+/// its direct children, is preferred over one that was not meant for the value, then the
+/// branch whose best error is deepest into the instance, then the one with the fewest
+/// errors. Ties go to the first branch.
+pub(crate) fn best_branch(states: &[ValidationState], path: &str) -> Option<usize> {
+    let mut best = None;
+    let mut best_key = None;
+    for (idx, state) in states.iter().enumerate() {
+        let deepest = match best_error(&state.errors) {
+            Some(error) => depth(error.get_path()),
+            None => continue,
+        };
+        let key = (
+            is_matched(state, path),
+            deepest,
+            std::cmp::Reverse(state.errors.len()),
+        );
+        if best_key.map(|best_key| key > best_key).unwrap_or(true) {
+            best = Some(idx);
+            best_key = Some(key);
+        }
+    }
+    best
+}
+
+/// Follows a failed `anyOf` or `oneOf` down to the best error of its best branch.
+fn innermost(error: &dyn ValicoError) -> &dyn ValicoError {
+    let states = if let Some(any_of) = error.downcast_ref::<errors::AnyOf>() {
+        &any_of.states
+    } else if let Some(one_of) = error.downcast_ref::<errors::OneOf>() {
+        // the error is about the matching branches if there are any
+        if one_of.matched > 0 {
+            return error;
+        }
+        &one_of.states
+    } else {
+        return error;
+    };
+    best_branch(states, error.get_path())
+        .and_then(|idx| best_error(&states[idx].errors))
+        .unwrap_or(error)
+}
+
+/// Whether the branch was meant for the value at `path`, i.e. the value has the right
+/// type and its properties the right constants.
+fn is_matched(state: &ValidationState, path: &str) -> bool {
+    !state.errors.iter().any(|error| {
+        let rest = match error.get_path().strip_prefix(path) {
+            Some(rest) => rest,
+            None => return false,
+        };
+        match error.get_code() {
+            "wrong_type" => rest.is_empty(),
+            code => {
+                DISCRIMINATING_CODES.contains(&code)
+                    && (rest.is_empty() || (rest.starts_with('/') && !rest[1..].contains('/')))
+            }
+        }
+    })
+}
+
+fn depth(path: &str) -> usize {
+    path.matches('/').count()
+}
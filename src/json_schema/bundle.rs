@@ -0,0 +1,381 @@
+//! Bundling of a schema and all documents it references into a single document.
+
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use url::Url;
+
+use super::helpers;
+use super::schema::{Schema, SchemaError};
+use super::scope::Scope;
+use super::SchemaVersion;
+
+/// The characters that are escaped in the JSON pointers of rewritten references.
+const FRAGMENT_SET: percent_encoding::AsciiSet = percent_encoding::CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'<')
+    .add(b'>')
+    .add(b'`')
+    .add(b'%');
+
+/// The keywords that identify a schema, they are dropped from the embedded schemas as
+/// all references are rewritten to JSON pointers.
+const IDENTIFIERS: [&str; 2] = ["$anchor", "$dynamicAnchor"];
+
+/// The changes to a document when it is bundled.
+#[derive(Default)]
+struct Edits<'s> {
+    /// The referencing schemas with the document and the JSON pointer of their target.
+    references: Vec<(&'s str, &'s str, &'s str)>,
+    /// The schemas that have an identifier.
+    identified: Vec<&'s str>,
+}
+
+/// Bundles the document with the given id, see `Scope::bundle`.
+pub(crate) fn bundle(scope: &Scope, id: &Url) -> Result<Value, SchemaError> {
+    let (root_key, root) = scope
+        .find(id)
+        .filter(|(_, schema)| schema.pointer.is_empty())
+        .ok_or(SchemaError::WrongId)?;
+    let version = root.version;
+    let definitions = if version >= SchemaVersion::Draft2019_09 {
+        "$defs"
+    } else {
+        "definitions"
+    };
+
+    let mut names: HashSet<String> = match root.original.get(definitions) {
+        Some(Value::Object(existing)) => existing.keys().cloned().collect(),
+        Some(_) => {
+            return Err(SchemaError::Malformed {
+                path: format!("/{definitions}"),
+                detail: "The value of this keyword MUST be an object.".to_string(),
+            })
+        }
+        None => HashSet::new(),
+    };
+
+    // the referenced documents are embedded under a name, and found at a JSON pointer
+    // within the bundle
+    let mut documents = vec![(root_key.as_str(), root, None)];
+    let mut prefixes: HashMap<&str, String> = HashMap::new();
+    prefixes.insert(root_key, String::new());
+    let mut edits = vec![];
+    while edits.len() < documents.len() {
+        let mut document_edits = Edits::default();
+        collect_edits(
+            scope,
+            documents[edits.len()].1,
+            version,
+            &mut document_edits,
+        )?;
+        for (_, target_document, _) in document_edits.references.iter() {
+            if !prefixes.contains_key(target_document) {
+                let name = unique_name(target_document, &mut names);
+                prefixes.insert(
+                    target_document,
+                    format!("/{}/{}", definitions, escape(&name)),
+                );
+                let target = scope.document(target_document).unwrap();
+                documents.push((target_document, target, Some(name)));
+            }
+        }
+        edits.push(document_edits);
+    }
+
+    let mut bundle = Value::Null;
+    let mut embedded = Map::new();
+    for ((_, document, name), edits) in documents.into_iter().zip(edits.iter()) {
+        let mut value = document.original.clone();
+        for (pointer, target_document, target) in edits.references.iter() {
+            let target = format!("{}{}", prefixes[target_document], target);
+            let reference = percent_encoding::utf8_percent_encode(&target, &FRAGMENT_SET);
+            if let Some(Value::Object(object)) = value.pointer_mut(pointer) {
+                object.insert("$ref".to_string(), Value::String(format!("#{reference}")));
+            }
+        }
+        for pointer in edits.identified.iter() {
+            // the root keeps its id, as the base of the rewritten references
+            if pointer.is_empty() && name.is_none() {
+                continue;
+            }
+            if let Some(Value::Object(object)) = value.pointer_mut(pointer) {
+                object.remove(helpers::id_key(version));
+                for key in IDENTIFIERS.iter() {
+                    object.remove(*key);
+                }
+                if pointer.is_empty() {
+                    object.remove("$schema");
+                }
+            }
+        }
+
+        match name {
+            Some(name) => {
+                embedded.insert(name, value);
+            }
+            None => bundle = value,
+        }
+    }
+
+    if !embedded.is_empty() {
+        bundle
+            .as_object_mut()
+            .unwrap()
+            .entry(definitions)
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+            .unwrap()
+            .extend(embedded);
+    }
+    Ok(bundle)
+}
+
+/// Collects the references and identifiers of a document and of all of its subschemas.
+fn collect_edits<'s>(
+    scope: &'s Scope,
+    schema: &'s Schema,
+    version: SchemaVersion,
+    edits: &mut Edits<'s>,
+) -> Result<(), SchemaError> {
+    if !schema.references.is_empty() || schema.id.is_some() {
+        if schema.version != version {
+            return Err(SchemaError::Malformed {
+                path: schema.pointer.clone(),
+                detail: "Schemas of different drafts cannot be bundled.".to_string(),
+            });
+        }
+        if ["$recursiveRef", "$dynamicRef"]
+            .iter()
+            .any(|key| schema.original.get(key).is_some())
+        {
+            return Err(SchemaError::Malformed {
+                path: schema.pointer.clone(),
+                detail: "Dynamic references cannot be bundled.".to_string(),
+            });
+        }
+    }
+    if schema.id.is_some() || schema.pointer.is_empty() {
+        edits.identified.push(&schema.pointer);
+    }
+    if let Some(reference) = schema.original.get("$ref").and_then(Value::as_str) {
+        if !schema.references.is_empty() {
+            let url = schema
+                .resource
+                .join(reference)
+                .map_err(SchemaError::UrlParseError)?;
+            let (document, target) = scope.find(&url).ok_or_else(|| SchemaError::Malformed {
+                path: schema.pointer.clone(),
+                detail: format!("The reference {url} cannot be resolved."),
+            })?;
+            edits
+                .references
+                .push((&schema.pointer, document, &target.pointer));
+        }
+    }
+
+    for subschema in schema.tree.values() {
+        collect_edits(scope, subschema, version, edits)?;
+    }
+    Ok(())
+}
+
+/// Derives the name of an embedded document from its URL, e.g. `address` for
+/// `http://example.com/schemas/address.json`.
+fn unique_name(document: &str, names: &mut HashSet<String>) -> String {
+    let url = Url::parse(document).ok();
+    let name = url
+        .as_ref()
+        .and_then(|url| url.path_segments())
+        .and_then(|segments| segments.rev().find(|segment| !segment.is_empty()))
+        .map(|segment| segment.trim_end_matches(".json").to_string())
+        .filter(|name| !name.is_empty())
+        .or_else(|| url.as_ref().and_then(Url::host_str).map(str::to_string))
+        .unwrap_or_else(|| "schema".to_string());
+    let name = percent_encoding::percent_decode_str(&name)
+        .decode_utf8_lossy()
+        .to_string();
+
+    let mut unique = name.clone();
+    let mut counter = 1;
+    while names.contains(&unique) {
+        counter += 1;
+        unique = format!("{name}_{counter}");
+    }
+    names.insert(unique.clone());
+    unique
+}
+
+/// Escapes a key for use in a JSON pointer.
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+fn assert_bundle_validates(
+    scope: &Scope,
+    id: &Url,
+    version: SchemaVersion,
+    instances: &[Value],
+) -> Value {
+    let bundle = scope.bundle(id).unwrap();
+    let mut bundled = Scope::new().set_version(version);
+    let bundle_id = bundled.compile(bundle.clone(), false).unwrap();
+    assert!(bundled.check_references().is_empty());
+    let schema = bundled.resolve(&bundle_id).unwrap();
+    let original = scope.resolve(id).unwrap();
+    for instance in instances {
+        let expected = original.validate(instance);
+        let state = schema.validate(instance);
+        assert_eq!(state.is_valid(), expected.is_valid(), "{instance}");
+        assert!(state.missing.is_empty());
+        let paths = |state: &super::ValidationState| {
+            let mut paths: Vec<String> = state
+                .errors
+                .iter()
+                .map(|error| error.get_path().to_string())
+                .collect();
+            paths.sort();
+            paths
+        };
+        assert_eq!(paths(&state), paths(&expected), "{instance}");
+    }
+    bundle
+}
+
+#[test]
+fn bundle_documents() {
+    let mut scope = Scope::new().set_version(SchemaVersion::Draft2019_09);
+    scope
+        .compile_with_id(
+            &Url::parse("http://example.com/schemas/common/address.json").unwrap(),
+            json!({
+                "$defs": {
+                    "zip": { "type": "string", "pattern": "^[0-9]{5}$" },
+                    "street": { "$anchor": "street", "type": "string" }
+                },
+                "properties": {
+                    "zip": { "$ref": "#/$defs/zip" },
+                    "street": { "$ref": "#street" },
+                    "resident": { "$ref": "../person.json" }
+                }
+            }),
+            false,
+        )
+        .unwrap();
+    scope
+        .compile_with_id(
+            &Url::parse("http://example.com/other/address.json").unwrap(),
+            json!({ "required": ["zip"] }),
+            false,
+        )
+        .unwrap();
+    let id = Url::parse("http://example.com/schemas/person.json").unwrap();
+    scope
+        .compile_with_id(
+            &id,
+            json!({
+                "$id": "http://example.com/schemas/person.json",
+                "$defs": { "address": { "type": "object" } },
+                "properties": {
+                    "home": { "$ref": "common/address.json" },
+                    "work": {
+                        "allOf": [
+                            { "$ref": "/other/address.json" },
+                            { "$ref": "common/address.json" }
+                        ]
+                    },
+                    "zip": { "$ref": "common/address.json#/$defs/zip" },
+                    "nested": {
+                        "$id": "nested/",
+                        "properties": { "zip": { "$ref": "../common/address.json#/$defs/zip" } }
+                    }
+                }
+            }),
+            false,
+        )
+        .unwrap();
+
+    let bundle = assert_bundle_validates(
+        &scope,
+        &id,
+        SchemaVersion::Draft2019_09,
+        &[
+            json!({}),
+            json!({ "home": { "zip": "12345", "street": "Main" }, "zip": "54321" }),
+            json!({ "home": { "zip": "123", "street": 1 }, "zip": 1 }),
+            json!({ "work": { "street": "Main" }, "nested": { "zip": "x" } }),
+            json!({ "home": { "resident": { "home": { "zip": 1 } } } }),
+        ],
+    );
+    assert_eq!(
+        bundle.pointer("/properties/home/$ref"),
+        Some(&json!("#/$defs/address_2"))
+    );
+    assert_eq!(
+        bundle.pointer("/properties/work/allOf/0/$ref"),
+        Some(&json!("#/$defs/address_3"))
+    );
+    assert_eq!(
+        bundle.pointer("/$defs/address_2/properties/resident/$ref"),
+        Some(&json!("#"))
+    );
+    assert_eq!(
+        bundle.pointer("/$defs/address_2/properties/street/$ref"),
+        Some(&json!("#/$defs/address_2/$defs/street"))
+    );
+    assert_eq!(
+        bundle.pointer("/$defs/address_2/$defs/street/$anchor"),
+        None
+    );
+    assert_eq!(bundle.pointer("/properties/nested/$id"), None);
+    assert_eq!(
+        bundle["$id"],
+        json!("http://example.com/schemas/person.json")
+    );
+}
+
+#[test]
+fn bundle_definitions() {
+    let mut scope = Scope::new();
+    scope
+        .compile_with_id(
+            &Url::parse("http://example.com/name%20type").unwrap(),
+            json!({ "definitions": { "a b": { "type": "string" } } }),
+            false,
+        )
+        .unwrap();
+    let id = scope
+        .compile(
+            json!({
+                "items": { "$ref": "http://example.com/name%20type#/definitions/a%20b" }
+            }),
+            false,
+        )
+        .unwrap();
+    let bundle = assert_bundle_validates(
+        &scope,
+        &id,
+        SchemaVersion::Draft7,
+        &[json!(["a"]), json!([1, "b", 2]), json!(1)],
+    );
+    assert_eq!(
+        bundle["items"]["$ref"],
+        json!("#/definitions/name%20type/definitions/a%20b")
+    );
+
+    let id = scope
+        .compile(
+            json!({
+                "$schema": "http://json-schema.org/draft/2019-09/schema",
+                "$ref": "http://example.com/name%20type"
+            }),
+            false,
+        )
+        .unwrap();
+    assert!(matches!(
+        scope.bundle(&id),
+        Err(SchemaError::Malformed { .. })
+    ));
+}
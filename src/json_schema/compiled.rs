@@ -0,0 +1,131 @@
+//! Compiled schemas that own their scope, see `CompiledSchema`.
+
+use serde_json::Value;
+use std::sync::Arc;
+use url::Url;
+
+use super::output;
+use super::schema;
+use super::scope::Scope;
+use super::validators::ValidationState;
+
+/// A compiled schema that owns (a share of) its `Scope`.
+///
+/// Unlike `ScopedSchema` it does not borrow anything, so it can be kept in application
+/// state, returned from constructors or sent to other threads.
+#[derive(Debug, Clone)]
+pub struct CompiledSchema {
+    scope: Arc<Scope>,
+    id: Url,
+}
+
+impl CompiledSchema {
+    /// Returns the schema with the given id, or `None` if the scope does not contain it.
+    pub fn new(scope: Arc<Scope>, id: Url) -> Option<CompiledSchema> {
+        scope.resolve(&id)?;
+        Some(CompiledSchema { scope, id })
+    }
+
+    /// Compiles a schema into the scope and takes ownership of it.
+    pub fn compile(
+        mut scope: Scope,
+        def: Value,
+        ban_unknown: bool,
+    ) -> Result<CompiledSchema, schema::SchemaError> {
+        let id = scope.compile(def, ban_unknown)?;
+        Ok(CompiledSchema {
+            scope: Arc::new(scope),
+            id,
+        })
+    }
+
+    pub fn id(&self) -> &Url {
+        &self.id
+    }
+
+    pub fn scope(&self) -> &Arc<Scope> {
+        &self.scope
+    }
+
+    /// Borrows the schema, e.g. to access its properties.
+    pub fn schema(&self) -> schema::ScopedSchema<'_> {
+        self.scope.resolve(&self.id).unwrap()
+    }
+
+    pub fn validate(&self, data: &Value) -> ValidationState {
+        self.schema().validate(data)
+    }
+
+    pub fn validate_in(&self, data: &Value, path: &str) -> ValidationState {
+        self.schema().validate_in(data, path)
+    }
+
+    pub fn validate_with_output(
+        &self,
+        data: &Value,
+        format: output::OutputFormat,
+    ) -> output::OutputUnit {
+        self.schema().validate_with_output(data, format)
+    }
+
+    pub fn validate_with_limit(&self, data: &Value, max_errors: usize) -> ValidationState {
+        self.schema().validate_with_limit(data, max_errors)
+    }
+
+    pub fn is_valid(&self, data: &Value) -> bool {
+        self.schema().is_valid(data)
+    }
+}
+
+#[test]
+fn owned_validators() {
+    struct Service {
+        users: CompiledSchema,
+        names: CompiledSchema,
+    }
+
+    impl Service {
+        fn new() -> Service {
+            let mut scope = Scope::new();
+            let users = scope
+                .compile(
+                    json!({
+                        "$id": "http://example.com/user",
+                        "properties": { "name": { "$ref": "name" } },
+                        "required": ["name"]
+                    }),
+                    false,
+                )
+                .unwrap();
+            let names = scope
+                .compile(
+                    json!({ "$id": "http://example.com/name", "type": "string" }),
+                    false,
+                )
+                .unwrap();
+            let scope = Arc::new(scope);
+            Service {
+                users: CompiledSchema::new(scope.clone(), users).unwrap(),
+                names: CompiledSchema::new(scope, names).unwrap(),
+            }
+        }
+    }
+
+    let service = Service::new();
+    assert!(service.users.is_valid(&json!({ "name": "Alice" })));
+    assert!(!service.users.is_valid(&json!({ "name": 1 })));
+    assert_eq!(
+        service.users.validate_in(&json!({}), "/user").errors[0].get_path(),
+        "/user/name"
+    );
+    assert!(service.names.is_valid(&json!("Bob")));
+    assert!(CompiledSchema::new(
+        service.names.scope().clone(),
+        Url::parse("http://example.com/unknown").unwrap()
+    )
+    .is_none());
+
+    let schema = CompiledSchema::compile(Scope::new(), json!({ "minimum": 3 }), false).unwrap();
+    let thread = std::thread::spawn(move || schema.is_valid(&json!(5)));
+    assert!(thread.join().unwrap());
+}
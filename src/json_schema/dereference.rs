@@ -0,0 +1,311 @@
+//! Inlining of the targets of `$ref`, see `Scope::dereference`.
+
+use serde_json::{Map, Value};
+use std::ptr;
+use url::Url;
+
+use super::helpers;
+use super::schema::{Schema, SchemaError};
+use super::scope::Scope;
+use super::SchemaVersion;
+
+/// What `Scope::dereference` does with references that would have to be inlined into
+/// their own target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecursiveRefs {
+    /// Leaves such references as `$ref`, with the absolute URL of the target (or `#` if
+    /// the target is the dereferenced schema itself).
+    Keep,
+    /// Fails with `SchemaError::Malformed` at the first such reference.
+    Reject,
+}
+
+/// The result of following a reference.
+enum Inlined {
+    /// The expanded target.
+    Content(Value),
+    /// The reference to keep, as it is recursive.
+    Recursive(String),
+}
+
+struct Dereferencer<'s> {
+    scope: &'s Scope,
+    root: &'s Schema,
+    recursive: RecursiveRefs,
+    /// The schemas that are being inlined, starting with the root.
+    stack: Vec<&'s Schema>,
+    /// The number of schema resources with their own `$id` that the current schema is
+    /// nested in, where `#` would not refer to the root anymore.
+    resources: usize,
+}
+
+/// Dereferences the schema with the given id, see `Scope::dereference`.
+pub(crate) fn dereference(
+    scope: &Scope,
+    id: &Url,
+    recursive: RecursiveRefs,
+) -> Result<Value, SchemaError> {
+    let (_, root) = scope.find(id).ok_or(SchemaError::WrongId)?;
+    let mut dereferencer = Dereferencer {
+        scope,
+        root,
+        recursive,
+        stack: vec![root],
+        resources: 0,
+    };
+    dereferencer.expand(root, "")
+}
+
+impl<'s> Dereferencer<'s> {
+    /// Returns the original value of `schema` with all references in and below it
+    /// inlined, `path` is its JSON pointer within the result.
+    fn expand(&mut self, schema: &'s Schema, path: &str) -> Result<Value, SchemaError> {
+        let is_resource = !ptr::eq(schema, self.root)
+            && schema
+                .original
+                .get(helpers::id_key(schema.version))
+                .and_then(Value::as_str)
+                .map(|id| !id.starts_with('#'))
+                .unwrap_or(false);
+        if is_resource {
+            self.resources += 1;
+        }
+        let expanded = self.expand_schema(schema, path);
+        if is_resource {
+            self.resources -= 1;
+        }
+        expanded
+    }
+
+    fn expand_schema(&mut self, schema: &'s Schema, path: &str) -> Result<Value, SchemaError> {
+        let reference = schema
+            .original
+            .get("$ref")
+            .and_then(Value::as_str)
+            .filter(|_| !schema.references.is_empty());
+        let inlined = match reference {
+            Some(reference) => self.inline(schema, reference, path)?,
+            None => return self.expand_subschemas(schema, path),
+        };
+
+        // the keywords next to `$ref` are ignored before draft 2019-09, and apply
+        // together with the target since
+        if schema.version < SchemaVersion::Draft2019_09 {
+            return Ok(match inlined {
+                Inlined::Content(content) => content,
+                Inlined::Recursive(reference) => json!({ "$ref": reference }),
+            });
+        }
+        let mut value = self.expand_subschemas(schema, path)?;
+        let object = value.as_object_mut().unwrap();
+        let content = match inlined {
+            Inlined::Content(content) => content,
+            Inlined::Recursive(reference) => {
+                object.insert("$ref".to_string(), Value::String(reference));
+                return Ok(value);
+            }
+        };
+        object.remove("$ref");
+        if object.is_empty() {
+            return Ok(content);
+        }
+        match object.get_mut("allOf") {
+            Some(Value::Array(all_of)) => all_of.push(content),
+            Some(_) => return Ok(json!({ "allOf": [value, content] })),
+            None => {
+                object.insert("allOf".to_string(), json!([content]));
+            }
+        }
+        Ok(value)
+    }
+
+    /// Returns the expanded target of a reference, or the reference to keep if it is
+    /// recursive.
+    fn inline(
+        &mut self,
+        schema: &'s Schema,
+        reference: &str,
+        path: &str,
+    ) -> Result<Inlined, SchemaError> {
+        let url = schema
+            .resource
+            .join(reference)
+            .map_err(SchemaError::UrlParseError)?;
+        let (_, target) = self
+            .scope
+            .find(&url)
+            .ok_or_else(|| SchemaError::Malformed {
+                path: path.to_string(),
+                detail: format!("The reference {url} cannot be resolved."),
+            })?;
+
+        if self.stack.iter().any(|schema| ptr::eq(*schema, target)) {
+            return match self.recursive {
+                RecursiveRefs::Keep if ptr::eq(target, self.root) && self.resources == 0 => {
+                    Ok(Inlined::Recursive("#".to_string()))
+                }
+                RecursiveRefs::Keep => Ok(Inlined::Recursive(url.to_string())),
+                RecursiveRefs::Reject => Err(SchemaError::Malformed {
+                    path: path.to_string(),
+                    detail: format!("The reference {url} is recursive."),
+                }),
+            };
+        }
+
+        self.stack.push(target);
+        let content = self.expand(target, path)?;
+        self.stack.pop();
+        Ok(Inlined::Content(content))
+    }
+
+    /// Returns the original value of `schema` with its subschemas expanded.
+    fn expand_subschemas(&mut self, schema: &'s Schema, path: &str) -> Result<Value, SchemaError> {
+        Ok(match schema.original {
+            Value::Object(ref object) => {
+                let mut expanded = Map::new();
+                for (key, value) in object.iter() {
+                    let value = match schema.tree.get(&helpers::encode(key)) {
+                        Some(subschema) if !value.is_boolean() => {
+                            let escaped = key.replace('~', "~0").replace('/', "~1");
+                            self.expand(subschema, &format!("{path}/{escaped}"))?
+                        }
+                        _ => value.clone(),
+                    };
+                    expanded.insert(key.clone(), value);
+                }
+                Value::Object(expanded)
+            }
+            Value::Array(ref array) => {
+                let mut expanded = vec![];
+                for (idx, value) in array.iter().enumerate() {
+                    let value = match schema.tree.get(&idx.to_string()) {
+                        Some(subschema) if !value.is_boolean() => {
+                            self.expand(subschema, &format!("{path}/{idx}"))?
+                        }
+                        _ => value.clone(),
+                    };
+                    expanded.push(value);
+                }
+                Value::Array(expanded)
+            }
+            ref value => value.clone(),
+        })
+    }
+}
+
+#[test]
+fn dereference_documents() {
+    let mut scope = Scope::new();
+    scope
+        .compile_with_id(
+            &Url::parse("http://example.com/address").unwrap(),
+            json!({
+                "definitions": { "zip": { "type": "string", "pattern": "^[0-9]{5}$" } },
+                "properties": { "zip": { "$ref": "#/definitions/zip" } }
+            }),
+            false,
+        )
+        .unwrap();
+    let id = scope
+        .compile(
+            json!({
+                "properties": {
+                    "home": { "$ref": "http://example.com/address", "type": "string" },
+                    "zips": { "items": [{ "$ref": "http://example.com/address#/definitions/zip" }, true] }
+                }
+            }),
+            false,
+        )
+        .unwrap();
+
+    let zip = json!({ "type": "string", "pattern": "^[0-9]{5}$" });
+    let dereferenced = scope.dereference(&id, RecursiveRefs::Reject).unwrap();
+    assert_eq!(
+        dereferenced,
+        json!({
+            "properties": {
+                "home": {
+                    "definitions": { "zip": zip },
+                    "properties": { "zip": zip }
+                },
+                "zips": { "items": [zip, true] }
+            }
+        })
+    );
+
+    let mut plain = Scope::new();
+    let schema = plain.compile_and_return(dereferenced, false).unwrap();
+    let original = scope.resolve(&id).unwrap();
+    for instance in [
+        json!({ "home": { "zip": "12345" }, "zips": ["54321", 1] }),
+        json!({ "home": { "zip": "123" } }),
+        json!({ "zips": [1] }),
+    ] {
+        assert_eq!(
+            schema.validate(&instance).is_valid(),
+            original.validate(&instance).is_valid()
+        );
+    }
+}
+
+#[test]
+fn dereference_recursive() {
+    let mut scope = Scope::new().set_version(SchemaVersion::Draft2020_12);
+    let id = scope
+        .compile(
+            json!({
+                "$defs": {
+                    "node": {
+                        "type": "object",
+                        "properties": {
+                            "value": { "$ref": "#/$defs/value", "minimum": 0 },
+                            "next": { "$ref": "#/$defs/node" },
+                            "root": { "$ref": "#" }
+                        }
+                    },
+                    "value": { "type": "integer" }
+                },
+                "$ref": "#/$defs/node"
+            }),
+            false,
+        )
+        .unwrap();
+
+    let node = scope
+        .dereference(
+            &Url::parse(&format!("{id}#/$defs/node")).unwrap(),
+            RecursiveRefs::Keep,
+        )
+        .unwrap();
+    assert_eq!(
+        node,
+        json!({
+            "type": "object",
+            "properties": {
+                "value": { "minimum": 0, "allOf": [{ "type": "integer" }] },
+                "next": { "$ref": "#" },
+                "root": {
+                    "$defs": {
+                        "node": {
+                            "type": "object",
+                            "properties": {
+                                "value": { "minimum": 0, "allOf": [{ "type": "integer" }] },
+                                "next": { "$ref": "#" },
+                                "root": { "$ref": format!("{id}#") }
+                            }
+                        },
+                        "value": { "type": "integer" }
+                    },
+                    "$ref": "#"
+                }
+            }
+        })
+    );
+
+    match scope.dereference(&id, RecursiveRefs::Reject) {
+        Err(SchemaError::Malformed { path, .. }) => {
+            assert_eq!(path, "/properties/next")
+        }
+        result => panic!("unexpected {:?}", result),
+    }
+}
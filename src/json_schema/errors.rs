@@ -1,157 +1,287 @@
+pub use super::super::common::error::ErrorLocation;
 use super::super::common::error::ValicoError;
 use serde::{Serialize, Serializer};
 use serde_json::{to_value, Value};
+use std::sync::Arc;
+
+use super::PrimitiveType;
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct WrongType {
     pub path: String,
+    pub location: ErrorLocation,
     pub detail: String,
+    /// The types allowed by the schema, empty if the error does not stem from `type`.
+    pub expected: Vec<PrimitiveType>,
+    /// The type of the value, if known.
+    pub actual: Option<PrimitiveType>,
 }
-impl_err!(WrongType, "wrong_type", "Type of the value is wrong", +detail);
-impl_serialize!(WrongType);
+impl_err!(WrongType, "wrong_type", "Type of the value is wrong", +detail, +location);
+impl_serialize!(
+    WrongType,
+    |err: &WrongType, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("expected".to_string(), to_value(&err.expected).unwrap()),
+    |err: &WrongType, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("actual".to_string(), to_value(err.actual).unwrap())
+);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct MultipleOf {
     pub path: String,
+    pub location: ErrorLocation,
+    /// The divisor set by the keyword.
+    pub multiple_of: serde_json::Number,
+    pub actual: serde_json::Number,
 }
-impl_err!(MultipleOf, "multiple_of", "Wrong number of the value");
-impl_serialize!(MultipleOf);
+impl_err!(MultipleOf, "multiple_of", "Wrong number of the value", +location);
+impl_serialize!(
+    MultipleOf,
+    |err: &MultipleOf, map: &mut ::serde_json::Map<String, Value>| map.insert(
+        "multiple_of".to_string(),
+        to_value(&err.multiple_of).unwrap()
+    ),
+    |err: &MultipleOf, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("actual".to_string(), to_value(&err.actual).unwrap())
+);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct Maximum {
     pub path: String,
+    pub location: ErrorLocation,
+    /// The limit set by the keyword.
+    pub limit: serde_json::Number,
+    /// Whether the limit itself is excluded, as with `exclusiveMaximum`.
+    pub exclusive: bool,
+    pub actual: serde_json::Number,
 }
-impl_err!(Maximum, "maximum", "Maximum condition is not met");
-impl_serialize!(Maximum);
+impl_err!(Maximum, "maximum", "Maximum condition is not met", +location);
+impl_serialize!(
+    Maximum,
+    |err: &Maximum, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("limit".to_string(), to_value(&err.limit).unwrap()),
+    |err: &Maximum, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("exclusive".to_string(), to_value(err.exclusive).unwrap()),
+    |err: &Maximum, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("actual".to_string(), to_value(&err.actual).unwrap())
+);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct Minimum {
     pub path: String,
+    pub location: ErrorLocation,
+    /// The limit set by the keyword.
+    pub limit: serde_json::Number,
+    /// Whether the limit itself is excluded, as with `exclusiveMinimum`.
+    pub exclusive: bool,
+    pub actual: serde_json::Number,
 }
-impl_err!(Minimum, "minimum", "Minimum condition is not met");
-impl_serialize!(Minimum);
+impl_err!(Minimum, "minimum", "Minimum condition is not met", +location);
+impl_serialize!(
+    Minimum,
+    |err: &Minimum, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("limit".to_string(), to_value(&err.limit).unwrap()),
+    |err: &Minimum, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("exclusive".to_string(), to_value(err.exclusive).unwrap()),
+    |err: &Minimum, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("actual".to_string(), to_value(&err.actual).unwrap())
+);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct MaxLength {
     pub path: String,
+    pub location: ErrorLocation,
+    /// The limit set by the keyword.
+    pub limit: u64,
+    /// The length in characters of the value.
+    pub actual: u64,
 }
-impl_err!(MaxLength, "max_length", "MaxLength condition is not met");
-impl_serialize!(MaxLength);
+impl_err!(MaxLength, "max_length", "MaxLength condition is not met", +location);
+impl_serialize!(
+    MaxLength,
+    |err: &MaxLength, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("limit".to_string(), to_value(err.limit).unwrap()),
+    |err: &MaxLength, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("actual".to_string(), to_value(err.actual).unwrap())
+);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct MinLength {
     pub path: String,
+    pub location: ErrorLocation,
+    /// The limit set by the keyword.
+    pub limit: u64,
+    /// The length in characters of the value.
+    pub actual: u64,
 }
-impl_err!(MinLength, "min_length", "MinLength condition is not met");
-impl_serialize!(MinLength);
+impl_err!(MinLength, "min_length", "MinLength condition is not met", +location);
+impl_serialize!(
+    MinLength,
+    |err: &MinLength, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("limit".to_string(), to_value(err.limit).unwrap()),
+    |err: &MinLength, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("actual".to_string(), to_value(err.actual).unwrap())
+);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct Pattern {
     pub path: String,
+    pub location: ErrorLocation,
 }
-impl_err!(Pattern, "pattern", "Pattern condition is not met");
+impl_err!(Pattern, "pattern", "Pattern condition is not met", +location);
 impl_serialize!(Pattern);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct MaxItems {
     pub path: String,
+    pub location: ErrorLocation,
+    /// The limit set by the keyword.
+    pub limit: u64,
+    /// The number of items of the value.
+    pub actual: u64,
 }
-impl_err!(MaxItems, "max_items", "MaxItems condition is not met");
-impl_serialize!(MaxItems);
+impl_err!(MaxItems, "max_items", "MaxItems condition is not met", +location);
+impl_serialize!(
+    MaxItems,
+    |err: &MaxItems, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("limit".to_string(), to_value(err.limit).unwrap()),
+    |err: &MaxItems, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("actual".to_string(), to_value(err.actual).unwrap())
+);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct MinItems {
     pub path: String,
+    pub location: ErrorLocation,
+    /// The limit set by the keyword.
+    pub limit: u64,
+    /// The number of items of the value.
+    pub actual: u64,
 }
-impl_err!(MinItems, "min_items", "MinItems condition is not met");
-impl_serialize!(MinItems);
+impl_err!(MinItems, "min_items", "MinItems condition is not met", +location);
+impl_serialize!(
+    MinItems,
+    |err: &MinItems, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("limit".to_string(), to_value(err.limit).unwrap()),
+    |err: &MinItems, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("actual".to_string(), to_value(err.actual).unwrap())
+);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct UniqueItems {
     pub path: String,
+    pub location: ErrorLocation,
 }
-impl_err!(
-    UniqueItems,
-    "unique_items",
-    "UniqueItems condition is not met"
-);
+impl_err!(UniqueItems, "unique_items", "UniqueItems condition is not met", +location);
 impl_serialize!(UniqueItems);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct Items {
     pub path: String,
+    pub location: ErrorLocation,
     pub detail: String,
 }
-impl_err!(Items, "items", "Items condition is not met", +detail);
+impl_err!(Items, "items", "Items condition is not met", +detail, +location);
 impl_serialize!(Items);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct MaxProperties {
     pub path: String,
+    pub location: ErrorLocation,
+    /// The limit set by the keyword.
+    pub limit: u64,
+    /// The number of properties of the value.
+    pub actual: u64,
 }
-impl_err!(
+impl_err!(MaxProperties, "max_properties", "MaxProperties condition is not met", +location);
+impl_serialize!(
     MaxProperties,
-    "max_properties",
-    "MaxProperties condition is not met"
+    |err: &MaxProperties, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("limit".to_string(), to_value(err.limit).unwrap()),
+    |err: &MaxProperties, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("actual".to_string(), to_value(err.actual).unwrap())
 );
-impl_serialize!(MaxProperties);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct MinProperties {
     pub path: String,
+    pub location: ErrorLocation,
+    /// The limit set by the keyword.
+    pub limit: u64,
+    /// The number of properties of the value.
+    pub actual: u64,
 }
-impl_err!(
+impl_err!(MinProperties, "min_properties", "MinProperties condition is not met", +location);
+impl_serialize!(
     MinProperties,
-    "min_properties",
-    "MinProperties condition is not met"
+    |err: &MinProperties, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("limit".to_string(), to_value(err.limit).unwrap()),
+    |err: &MinProperties, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("actual".to_string(), to_value(err.actual).unwrap())
 );
-impl_serialize!(MinProperties);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct Required {
     pub path: String,
+    pub location: ErrorLocation,
 }
-impl_err!(Required, "required", "This property is required");
+impl_err!(Required, "required", "This property is required", +location);
 impl_serialize!(Required);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct Properties {
     pub path: String,
+    pub location: ErrorLocation,
     pub detail: String,
+    /// The additional property that is not allowed.
+    pub property: String,
+    /// The properties declared in `properties`, sorted by name.
+    pub known: Arc<[String]>,
 }
-impl_err!(Properties, "properties", "Property conditions are not met", +detail);
-impl_serialize!(Properties);
+impl_err!(Properties, "properties", "Property conditions are not met", +detail, +location);
+impl_serialize!(
+    Properties,
+    |err: &Properties, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("property".to_string(), to_value(&err.property).unwrap())
+);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct Enum {
     pub path: String,
+    pub location: ErrorLocation,
+    /// The values allowed by the keyword.
+    pub allowed: Vec<Value>,
 }
-impl_err!(Enum, "enum", "Enum conditions are not met");
-impl_serialize!(Enum);
+impl_err!(Enum, "enum", "Enum conditions are not met", +location);
+impl_serialize!(
+    Enum,
+    |err: &Enum, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("allowed".to_string(), to_value(&err.allowed).unwrap())
+);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct AnyOf {
     pub path: String,
+    pub location: ErrorLocation,
     pub states: Vec<super::validators::ValidationState>,
 }
-impl_err!(AnyOf, "any_of", "AnyOf conditions are not met");
+impl_err!(AnyOf, "any_of", "AnyOf conditions are not met", +location);
 impl_serialize!(
     AnyOf,
     |err: &AnyOf, map: &mut ::serde_json::Map<String, Value>| map
@@ -162,77 +292,132 @@ impl_serialize!(
 #[allow(missing_copy_implementations)]
 pub struct OneOf {
     pub path: String,
+    pub location: ErrorLocation,
     pub states: Vec<super::validators::ValidationState>,
+    /// The number of branches the value is valid against, the error is about these if
+    /// there is more than one.
+    pub matched: usize,
 }
-impl_err!(OneOf, "one_of", "OneOf conditions are not met");
+impl_err!(OneOf, "one_of", "OneOf conditions are not met", +location);
 impl_serialize!(
     OneOf,
     |err: &OneOf, map: &mut ::serde_json::Map<String, Value>| map
         .insert("states".to_string(), to_value(&err.states).unwrap())
 );
 
+#[derive(Debug)]
+#[allow(missing_copy_implementations)]
+pub struct UnknownDiscriminator {
+    pub path: String,
+    pub location: ErrorLocation,
+    /// The property that selects the branch of `anyOf` or `oneOf`.
+    pub property: String,
+    /// The values of the property that select a branch.
+    pub allowed: Vec<Value>,
+}
+impl_err!(UnknownDiscriminator, "discriminator", "Unknown discriminator value", +location);
+impl_serialize!(
+    UnknownDiscriminator,
+    |err: &UnknownDiscriminator, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("property".to_string(), to_value(&err.property).unwrap()),
+    |err: &UnknownDiscriminator, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("allowed".to_string(), to_value(&err.allowed).unwrap())
+);
+
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct Const {
     pub path: String,
+    pub location: ErrorLocation,
+    /// The value required by the keyword.
+    pub expected: Value,
 }
-impl_err!(Const, "const", "Const condition is not met");
-impl_serialize!(Const);
+impl_err!(Const, "const", "Const condition is not met", +location);
+impl_serialize!(
+    Const,
+    |err: &Const, map: &mut ::serde_json::Map<String, Value>| map
+        .insert("expected".to_string(), to_value(&err.expected).unwrap())
+);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct Contains {
     pub path: String,
+    pub location: ErrorLocation,
 }
-impl_err!(Contains, "contains", "Contains condition is not met");
+impl_err!(Contains, "contains", "Contains condition is not met", +location);
 impl_serialize!(Contains);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct ContainsMinMax {
     pub path: String,
+    pub location: ErrorLocation,
 }
-impl_err!(
-    ContainsMinMax,
-    "min_contains/max_contains",
-    "Contains minimum/maximum is not met"
-);
+impl_err!(ContainsMinMax, "min_contains/max_contains", "Contains minimum/maximum is not met", +location);
 impl_serialize!(ContainsMinMax);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct Not {
     pub path: String,
+    pub location: ErrorLocation,
 }
-impl_err!(Not, "not", "Not condition is not met");
+impl_err!(Not, "not", "Not condition is not met", +location);
 impl_serialize!(Not);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct DivergentDefaults {
     pub path: String,
+    pub location: ErrorLocation,
 }
-impl_err!(
-    DivergentDefaults,
-    "default",
-    "Application of defaults did not converge"
-);
+impl_err!(DivergentDefaults, "default", "Application of defaults did not converge", +location);
 impl_serialize!(DivergentDefaults);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct Format {
     pub path: String,
+    pub location: ErrorLocation,
     pub detail: String,
 }
-impl_err!(Format, "format", "Format is wrong", +detail);
+impl_err!(Format, "format", "Format is wrong", +detail, +location);
 impl_serialize!(Format);
 
 #[derive(Debug)]
 #[allow(missing_copy_implementations)]
 pub struct Unevaluated {
     pub path: String,
+    pub location: ErrorLocation,
     pub detail: String,
 }
-impl_err!(Unevaluated, "unevaluated", "Unevaluated condition is not met", +detail);
+impl_err!(Unevaluated, "unevaluated", "Unevaluated condition is not met", +detail, +location);
 impl_serialize!(Unevaluated);
+
+#[derive(Debug)]
+#[allow(missing_copy_implementations)]
+pub struct ReferenceCycle {
+    pub path: String,
+    pub location: ErrorLocation,
+}
+impl_err!(ReferenceCycle, "reference_cycle", "The schema refers to itself without consuming the value", +location);
+impl_serialize!(ReferenceCycle);
+
+#[derive(Debug)]
+#[allow(missing_copy_implementations)]
+pub struct MaxDepth {
+    pub path: String,
+    pub location: ErrorLocation,
+}
+impl_err!(MaxDepth, "max_depth", "The value is nested too deeply", +location);
+impl_serialize!(MaxDepth);
+
+#[derive(Debug)]
+#[allow(missing_copy_implementations)]
+pub struct MaxSteps {
+    pub path: String,
+    pub location: ErrorLocation,
+}
+impl_err!(MaxSteps, "max_steps", "The validation takes too many steps", +location);
+impl_serialize!(MaxSteps);
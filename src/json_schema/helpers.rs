@@ -4,6 +4,7 @@ use url::Url;
 use uuid::Uuid;
 
 use super::schema;
+use super::SchemaVersion;
 
 pub fn generate_id() -> Url {
     let uuid = Uuid::new_v4();
@@ -35,6 +36,15 @@ pub fn connect(strings: &[&str]) -> String {
         .join("/")
 }
 
+/// The keyword that identifies a schema, it only got its `$` prefix in draft 6.
+pub fn id_key(version: SchemaVersion) -> &'static str {
+    if version < SchemaVersion::Draft6 {
+        "id"
+    } else {
+        "$id"
+    }
+}
+
 pub fn parse_url_key(key: &str, obj: &Value) -> Result<Option<Url>, schema::SchemaError> {
     match obj.get(key) {
         Some(value) => match value.as_str() {
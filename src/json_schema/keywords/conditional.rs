@@ -3,11 +3,16 @@ use serde_json::Value;
 use super::super::helpers;
 use super::super::schema;
 use super::super::validators;
+use super::super::SchemaVersion;
 
 #[allow(missing_copy_implementations)]
 pub struct Conditional;
 impl super::Keyword for Conditional {
     fn compile(&self, def: &Value, ctx: &schema::WalkContext<'_>) -> super::KeywordResult {
+        if ctx.version < SchemaVersion::Draft7 {
+            return Ok(None);
+        }
+
         let maybe_if = def.get("if");
         let maybe_then = def.get("then");
         let maybe_else = def.get("else");
@@ -18,18 +23,21 @@ impl super::Keyword for Conditional {
             let if_ = helpers::alter_fragment_path(
                 ctx.url.clone(),
                 [ctx.escaped_fragment().as_ref(), "if"].join("/"),
-            );
+            )
+            .into();
             let then_ = maybe_then.map(|_| {
                 helpers::alter_fragment_path(
                     ctx.url.clone(),
                     [ctx.escaped_fragment().as_ref(), "then"].join("/"),
                 )
+                .into()
             });
             let else_ = maybe_else.map(|_| {
                 helpers::alter_fragment_path(
                     ctx.url.clone(),
                     [ctx.escaped_fragment().as_ref(), "else"].join("/"),
                 )
+                .into()
             });
             Ok(Some(Box::new(validators::Conditional {
                 if_,
@@ -2,11 +2,16 @@ use serde_json::Value;
 
 use super::super::schema;
 use super::super::validators;
+use super::super::SchemaVersion;
 
 #[allow(missing_copy_implementations)]
 pub struct Const;
 impl super::Keyword for Const {
-    fn compile(&self, def: &Value, _ctx: &schema::WalkContext) -> super::KeywordResult {
+    fn compile(&self, def: &Value, ctx: &schema::WalkContext) -> super::KeywordResult {
+        if ctx.version < SchemaVersion::Draft6 {
+            return Ok(None);
+        }
+
         let const_ = keyword_key_exists!(def, "const");
 
         Ok(Some(Box::new(validators::Const {
@@ -10,6 +10,10 @@ use super::super::validators;
 pub struct Contains;
 impl super::Keyword for Contains {
     fn compile(&self, def: &Value, ctx: &schema::WalkContext) -> super::KeywordResult {
+        if ctx.version < SchemaVersion::Draft6 {
+            return Ok(None);
+        }
+
         let contains = keyword_key_exists!(def, "contains");
         let (max_contains, min_contains) = if ctx.version >= SchemaVersion::Draft2019_09 {
             let max_contains = def
@@ -42,9 +46,13 @@ impl super::Keyword for Contains {
                 url: helpers::alter_fragment_path(
                     ctx.url.clone(),
                     [ctx.escaped_fragment().as_ref(), "contains"].join("/"),
-                ),
+                )
+                .into(),
                 max_contains,
                 min_contains,
+                max_contains_location: ctx.schema_location("maxContains"),
+                min_contains_location: ctx.schema_location("minContains"),
+                mark_evaluated: ctx.version >= SchemaVersion::Draft2020_12,
             })))
         } else {
             Err(schema::SchemaError::Malformed {
@@ -58,6 +66,7 @@ impl super::Keyword for Contains {
 #[cfg(test)]
 pub mod tests {
     use crate::json_schema::scope;
+    use crate::json_schema::SchemaVersion;
     use serde_json::Value;
 
     fn schema() -> Value {
@@ -97,4 +106,21 @@ pub mod tests {
         assert!(result.is_strictly_valid());
         assert_eq!(result.replacement, None);
     }
+
+    #[test]
+    fn contained_items_are_evaluated_in_draft_2020_12() {
+        let mut scope = scope::Scope::new().set_version(SchemaVersion::Draft2020_12);
+        let schema = scope
+            .compile_and_return(
+                json!({
+                    "prefixItems": [true],
+                    "contains": { "type": "string" },
+                    "unevaluatedItems": false
+                }),
+                true,
+            )
+            .unwrap();
+        assert!(schema.validate(&json!([1, "foo", "bar"])).is_valid());
+        assert!(!schema.validate(&json!([1, 2, "foo"])).is_valid());
+    }
 }
@@ -120,6 +120,11 @@ impl super::Keyword for ContentMedia {
             }
         }
 
-        Ok(Some(Box::new(validators::ContentMedia { type_, encoding })))
+        Ok(Some(Box::new(validators::ContentMedia {
+            type_,
+            encoding,
+            type_location: ctx.schema_location("contentMediaType"),
+            encoding_location: ctx.schema_location("contentEncoding"),
+        })))
     }
 }
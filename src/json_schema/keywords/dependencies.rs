@@ -5,6 +5,7 @@ use crate::json_schema::validators::dependencies::DepKind;
 use super::super::helpers;
 use super::super::schema;
 use super::super::validators;
+use super::super::SchemaVersion;
 
 enum DepsMode {
     AllowAny,
@@ -57,18 +58,27 @@ impl Dependencies {
         let deps = deps.as_object().unwrap();
         let mut items = vec![];
         for (key, item) in deps.iter() {
+            if item.is_boolean() && ctx.version < SchemaVersion::Draft6 {
+                return Err(schema::SchemaError::Malformed {
+                    path: ctx.fragment.join("/"),
+                    detail: "Boolean schemas are not supported before draft 6.".to_string(),
+                });
+            }
             if (item.is_object() || item.is_boolean()) && mode == DepsMode::DependentSchemas {
                 items.push((
                     key.clone(),
-                    validators::dependencies::DepKind::Schema(helpers::alter_fragment_path(
-                        ctx.url.clone(),
-                        [
-                            ctx.escaped_fragment().as_ref(),
-                            deps_key,
-                            helpers::encode(key).as_ref(),
-                        ]
-                        .join("/"),
-                    )),
+                    validators::dependencies::DepKind::Schema(
+                        helpers::alter_fragment_path(
+                            ctx.url.clone(),
+                            [
+                                ctx.escaped_fragment().as_ref(),
+                                deps_key,
+                                helpers::encode(key).as_ref(),
+                            ]
+                            .join("/"),
+                        )
+                        .into(),
+                    ),
                 ));
             } else if item.is_array() && mode == DepsMode::DependentRequired {
                 let item = item.as_array().unwrap();
@@ -95,3 +95,17 @@ fn malformed() {
         )
         .is_err());
 }
+
+#[test]
+fn enum_error_fields() {
+    let mut scope = scope::Scope::new();
+    let schema = scope
+        .compile_and_return(json!({ "enum": ["a", 1] }), true)
+        .unwrap();
+
+    let state = schema.validate(&json!("b"));
+    assert_eq!(
+        to_value(&state.errors).unwrap()[0]["allowed"],
+        json!(["a", 1])
+    );
+}
@@ -3,11 +3,16 @@ use serde_json::Value;
 use super::super::helpers;
 use super::super::schema;
 use super::super::validators;
+use super::super::SchemaVersion;
 
 #[allow(missing_copy_implementations)]
 pub struct Items;
 impl super::Keyword for Items {
     fn compile(&self, def: &Value, ctx: &schema::WalkContext<'_>) -> super::KeywordResult {
+        if ctx.version >= SchemaVersion::Draft2020_12 {
+            return compile_prefix_items(def, ctx);
+        }
+
         let maybe_items = def.get("items");
         let maybe_additional = def.get("additionalItems");
 
@@ -17,23 +22,29 @@ impl super::Keyword for Items {
 
         let items = if let Some(items_val) = maybe_items {
             Some(if items_val.is_object() || items_val.is_boolean() {
-                validators::items::ItemsKind::Schema(helpers::alter_fragment_path(
-                    ctx.url.clone(),
-                    [ctx.escaped_fragment().as_ref(), "items"].join("/"),
-                ))
+                validators::items::ItemsKind::Schema(
+                    helpers::alter_fragment_path(
+                        ctx.url.clone(),
+                        [ctx.escaped_fragment().as_ref(), "items"].join("/"),
+                    )
+                    .into(),
+                )
             } else if items_val.is_array() {
                 let mut schemas = vec![];
                 for (idx, item) in items_val.as_array().unwrap().iter().enumerate() {
                     if item.is_object() || item.is_boolean() {
-                        schemas.push(helpers::alter_fragment_path(
-                            ctx.url.clone(),
-                            [
-                                ctx.escaped_fragment().as_ref(),
-                                "items",
-                                idx.to_string().as_ref(),
-                            ]
-                            .join("/"),
-                        ))
+                        schemas.push(
+                            helpers::alter_fragment_path(
+                                ctx.url.clone(),
+                                [
+                                    ctx.escaped_fragment().as_ref(),
+                                    "items",
+                                    idx.to_string().as_ref(),
+                                ]
+                                .join("/"),
+                            )
+                            .into(),
+                        )
                     } else {
                         return Err(schema::SchemaError::Malformed {
                             path: ctx.fragment.join("/"),
@@ -57,10 +68,13 @@ impl super::Keyword for Items {
             Some(if additional_val.is_boolean() {
                 validators::items::AdditionalKind::Boolean(additional_val.as_bool().unwrap())
             } else if additional_val.is_object() {
-                validators::items::AdditionalKind::Schema(helpers::alter_fragment_path(
-                    ctx.url.clone(),
-                    [ctx.escaped_fragment().as_ref(), "additionalItems"].join("/"),
-                ))
+                validators::items::AdditionalKind::Schema(
+                    helpers::alter_fragment_path(
+                        ctx.url.clone(),
+                        [ctx.escaped_fragment().as_ref(), "additionalItems"].join("/"),
+                    )
+                    .into(),
+                )
             } else {
                 return Err(schema::SchemaError::Malformed {
                     path: ctx.fragment.join("/"),
@@ -74,6 +88,7 @@ impl super::Keyword for Items {
         Ok(Some(Box::new(validators::Items {
             items,
             additional: additional_items,
+            additional_location: ctx.schema_location("additionalItems"),
         })))
     }
 
@@ -82,6 +97,85 @@ impl super::Keyword for Items {
     }
 }
 
+/// Since draft 2020-12 the array form of `items` is replaced by `prefixItems`, and `items`
+/// takes over the role of `additionalItems` (which is no longer supported).
+fn compile_prefix_items(def: &Value, ctx: &schema::WalkContext<'_>) -> super::KeywordResult {
+    let maybe_prefix = def.get("prefixItems");
+    let maybe_items = def.get("items");
+
+    if !(maybe_prefix.is_some() || maybe_items.is_some()) {
+        return Ok(None);
+    }
+
+    let prefix = if let Some(prefix_val) = maybe_prefix {
+        let prefix_val = prefix_val
+            .as_array()
+            .ok_or_else(|| schema::SchemaError::Malformed {
+                path: ctx.fragment.join("/"),
+                detail: "`prefixItems` must be an array".to_string(),
+            })?;
+        let mut schemas = vec![];
+        for (idx, item) in prefix_val.iter().enumerate() {
+            if item.is_object() || item.is_boolean() {
+                schemas.push(
+                    helpers::alter_fragment_path(
+                        ctx.url.clone(),
+                        [
+                            ctx.escaped_fragment().as_ref(),
+                            "prefixItems",
+                            idx.to_string().as_ref(),
+                        ]
+                        .join("/"),
+                    )
+                    .into(),
+                )
+            } else {
+                return Err(schema::SchemaError::Malformed {
+                    path: ctx.fragment.join("/"),
+                    detail: "Items of this array MUST be objects or booleans".to_string(),
+                });
+            }
+        }
+        Some(schemas)
+    } else {
+        None
+    };
+
+    if let Some(items_val) = maybe_items {
+        if !(items_val.is_object() || items_val.is_boolean()) {
+            return Err(schema::SchemaError::Malformed {
+                path: ctx.fragment.join("/"),
+                detail: "`items` must be an object or a boolean".to_string(),
+            });
+        }
+    }
+    let items_url = || {
+        helpers::alter_fragment_path(
+            ctx.url.clone(),
+            [ctx.escaped_fragment().as_ref(), "items"].join("/"),
+        )
+        .into()
+    };
+
+    let validator = match prefix {
+        Some(schemas) => validators::Items {
+            items: Some(validators::items::ItemsKind::Array(schemas)),
+            additional: maybe_items.map(|items_val| match items_val.as_bool() {
+                Some(allowed) => validators::items::AdditionalKind::Boolean(allowed),
+                None => validators::items::AdditionalKind::Schema(items_url()),
+            }),
+            additional_location: ctx.schema_location("items"),
+        },
+        None => validators::Items {
+            items: Some(validators::items::ItemsKind::Schema(items_url())),
+            additional: None,
+            additional_location: ctx.schema_location("items"),
+        },
+    };
+
+    Ok(Some(Box::new(validator)))
+}
+
 #[cfg(test)]
 use super::super::builder;
 #[cfg(test)]
@@ -286,3 +380,72 @@ fn validate_items_with_array_of_schemes_with_additional_schema() {
         false
     );
 }
+
+#[test]
+fn validate_prefix_items() {
+    let mut scope = scope::Scope::new().set_version(SchemaVersion::Draft2020_12);
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "prefixItems": [
+                    { "type": "integer" },
+                    { "type": "string" }
+                ],
+                "items": { "type": "boolean" }
+            }),
+            true,
+        )
+        .unwrap();
+
+    assert!(schema.validate(&json!([1])).is_valid());
+    assert!(schema.validate(&json!([1, "a", true, false])).is_valid());
+    assert!(!schema.validate(&json!(["a", 1])).is_valid());
+    assert!(!schema.validate(&json!([1, "a", 2])).is_valid());
+}
+
+#[test]
+fn validate_prefix_items_with_items_false() {
+    let mut scope = scope::Scope::new().set_version(SchemaVersion::Draft2020_12);
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "prefixItems": [{ "type": "integer" }],
+                "items": false
+            }),
+            true,
+        )
+        .unwrap();
+
+    assert!(schema.validate(&json!([1])).is_valid());
+    assert!(!schema.validate(&json!([1, 2])).is_valid());
+}
+
+#[test]
+fn array_items_are_malformed_in_draft_2020_12() {
+    let mut scope = scope::Scope::new().set_version(SchemaVersion::Draft2020_12);
+    assert!(scope
+        .compile_and_return(json!({ "items": [{ "type": "integer" }] }), true)
+        .is_err());
+}
+
+#[test]
+fn default_for_prefix_items() {
+    let mut scope = scope::Scope::new()
+        .supply_defaults()
+        .set_version(SchemaVersion::Draft2020_12);
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "prefixItems": [
+                    { "type": "boolean", "default": true },
+                    { "type": "number", "default": 42 }
+                ]
+            }),
+            true,
+        )
+        .unwrap();
+    assert_eq!(schema.get_default(), Some(json!([true, 42])));
+    let result = schema.validate(&json!([false]));
+    assert!(result.is_strictly_valid());
+    assert_eq!(result.replacement, Some(json!([false, 42])));
+}
@@ -2,8 +2,42 @@ use serde_json::Value;
 
 use super::super::schema;
 use super::super::validators;
+use super::super::SchemaVersion;
 
 macro_rules! kw_minmax {
+    ($name:ident, $keyword:expr, $exclusive:ident, $exclusive_keyword:expr) => {
+        #[allow(missing_copy_implementations)]
+        pub struct $name;
+        impl super::Keyword for $name {
+            fn compile(&self, def: &Value, ctx: &schema::WalkContext<'_>) -> super::KeywordResult {
+                let value = keyword_key_exists!(def, $keyword);
+
+                if value.is_number() {
+                    let value = value.as_number().unwrap().clone();
+                    // in draft 4 the exclusive keyword is a boolean that modifies this one
+                    let exclusive = ctx.version == SchemaVersion::Draft4
+                        && def.get($exclusive_keyword).and_then(|v| v.as_bool()).unwrap_or(false);
+                    if exclusive {
+                        Ok(Some(Box::new(validators::$exclusive {
+                            number: value
+                        })))
+                    } else {
+                        Ok(Some(Box::new(validators::$name {
+                            number: value
+                        })))
+                    }
+                } else {
+                    Err(schema::SchemaError::Malformed {
+                        path: ctx.fragment.join("/"),
+                        detail: "the `minimum/maximum/exclusiveMinimum/exclusiveMaximum` value must be a number".to_string()
+                    })
+                }
+            }
+        }
+    }
+}
+
+macro_rules! kw_exclusive_minmax {
     ($name:ident, $keyword:expr) => {
         #[allow(missing_copy_implementations)]
         pub struct $name;
@@ -11,8 +45,20 @@ macro_rules! kw_minmax {
             fn compile(&self, def: &Value, ctx: &schema::WalkContext<'_>) -> super::KeywordResult {
                 let value = keyword_key_exists!(def, $keyword);
 
+                if ctx.version == SchemaVersion::Draft4 {
+                    // handled together with `minimum/maximum`
+                    return if value.is_boolean() {
+                        Ok(None)
+                    } else {
+                        Err(schema::SchemaError::Malformed {
+                            path: ctx.fragment.join("/"),
+                            detail: "the `exclusiveMinimum/exclusiveMaximum` value must be a boolean in draft 4".to_string()
+                        })
+                    };
+                }
+
                 if value.is_number() {
-                    let value = value.as_f64().unwrap();
+                    let value = value.as_number().unwrap().clone();
                     Ok(Some(Box::new(validators::$name {
                         number: value
                     })))
@@ -27,10 +73,10 @@ macro_rules! kw_minmax {
     }
 }
 
-kw_minmax!(Maximum, "maximum");
-kw_minmax!(ExclusiveMaximum, "exclusiveMaximum");
-kw_minmax!(Minimum, "minimum");
-kw_minmax!(ExclusiveMinimum, "exclusiveMinimum");
+kw_minmax!(Maximum, "maximum", ExclusiveMaximum, "exclusiveMaximum");
+kw_exclusive_minmax!(ExclusiveMaximum, "exclusiveMaximum");
+kw_minmax!(Minimum, "minimum", ExclusiveMinimum, "exclusiveMinimum");
+kw_exclusive_minmax!(ExclusiveMinimum, "exclusiveMinimum");
 
 #[cfg(test)]
 use super::super::builder;
@@ -175,3 +221,66 @@ fn mailformed_exclusive_minumum() {
         )
         .is_err());
 }
+
+#[test]
+fn validate_exclusive_maximum_draft4() {
+    let mut scope = scope::Scope::new().set_version(SchemaVersion::Draft4);
+    let schema = scope
+        .compile_and_return(json!({"maximum": 10, "exclusiveMaximum": true}), true)
+        .unwrap();
+
+    assert_eq!(schema.validate(&to_value(9).unwrap()).is_valid(), true);
+    assert_eq!(schema.validate(&to_value(10).unwrap()).is_valid(), false);
+
+    let schema = scope
+        .compile_and_return(json!({"minimum": 10, "exclusiveMinimum": false}), true)
+        .unwrap();
+
+    assert_eq!(schema.validate(&to_value(10).unwrap()).is_valid(), true);
+    assert_eq!(schema.validate(&to_value(9).unwrap()).is_valid(), false);
+}
+
+#[test]
+fn mailformed_exclusive_maximum_draft4() {
+    let mut scope = scope::Scope::new().set_version(SchemaVersion::Draft4);
+
+    assert!(scope
+        .compile_and_return(json!({"maximum": 10, "exclusiveMaximum": 10}), true)
+        .is_err());
+}
+
+#[test]
+fn maximum_error_fields() {
+    let mut scope = scope::Scope::new();
+    let schema = scope
+        .compile_and_return(
+            json!({ "$id": "http://example.com/maximum", "exclusiveMaximum": 10 }),
+            true,
+        )
+        .unwrap();
+
+    let state = schema.validate(&json!(42));
+    assert_eq!(
+        to_value(&state.errors).unwrap(),
+        json!([{
+            "code": "maximum",
+            "title": "Maximum condition is not met",
+            "path": "",
+            "keyword_location": "/exclusiveMaximum",
+            "schema_path": "/exclusiveMaximum",
+            "schema_url": "http://example.com/maximum#/exclusiveMaximum",
+            "limit": 10,
+            "exclusive": true,
+            "actual": 42
+        }])
+    );
+
+    // the numbers are reported as they are written, beyond the precision of a float
+    let schema = scope
+        .compile_and_return(json!({ "maximum": 9007199254740993u64 }), true)
+        .unwrap();
+    let state = schema.validate(&json!(9007199254740995u64));
+    let error = to_value(&state.errors).unwrap();
+    assert_eq!(error[0]["limit"], json!(9007199254740993u64));
+    assert_eq!(error[0]["actual"], json!(9007199254740995u64));
+}
@@ -82,6 +82,11 @@ pub fn default() -> KeywordMap {
     let mut map = collections::HashMap::new();
 
     decouple_keyword((vec!["$ref"], Box::new(ref_::Ref)), &mut map);
+    decouple_keyword(
+        (vec!["$recursiveRef"], Box::new(ref_::RecursiveRef)),
+        &mut map,
+    );
+    decouple_keyword((vec!["$dynamicRef"], Box::new(ref_::DynamicRef)), &mut map);
     decouple_keyword((vec!["allOf"], Box::new(of::AllOf)), &mut map);
     decouple_keyword((vec!["anyOf"], Box::new(of::AnyOf)), &mut map);
     decouple_keyword((vec!["const"], Box::new(const_::Const)), &mut map);
@@ -109,7 +114,10 @@ pub fn default() -> KeywordMap {
         &mut map,
     );
     decouple_keyword(
-        (vec!["items", "additionalItems"], Box::new(items::Items)),
+        (
+            vec!["items", "additionalItems", "prefixItems"],
+            Box::new(items::Items),
+        ),
         &mut map,
     );
     decouple_keyword(
@@ -206,6 +214,103 @@ pub fn default() -> KeywordMap {
     map
 }
 
+/// The standard vocabularies this implementation knows about. The core vocabularies are
+/// always enabled, the others only if the metaschema of a schema declares them.
+pub const KNOWN_VOCABULARIES: &[&str] = &[
+    "https://json-schema.org/draft/2019-09/vocab/core",
+    "https://json-schema.org/draft/2019-09/vocab/applicator",
+    "https://json-schema.org/draft/2019-09/vocab/validation",
+    "https://json-schema.org/draft/2019-09/vocab/meta-data",
+    "https://json-schema.org/draft/2019-09/vocab/format",
+    "https://json-schema.org/draft/2019-09/vocab/content",
+    "https://json-schema.org/draft/2020-12/vocab/core",
+    "https://json-schema.org/draft/2020-12/vocab/applicator",
+    "https://json-schema.org/draft/2020-12/vocab/unevaluated",
+    "https://json-schema.org/draft/2020-12/vocab/validation",
+    "https://json-schema.org/draft/2020-12/vocab/meta-data",
+    "https://json-schema.org/draft/2020-12/vocab/format-annotation",
+    "https://json-schema.org/draft/2020-12/vocab/format-assertion",
+    "https://json-schema.org/draft/2020-12/vocab/content",
+];
+
+/// Returns the vocabularies that define the given keyword in the given version.
+///
+/// Core keywords and custom keywords are not part of any vocabulary that can be switched
+/// off, so an empty slice is returned for them.
+pub fn vocabularies(key: &str, version: SchemaVersion) -> &'static [&'static str] {
+    match version {
+        SchemaVersion::Draft4 | SchemaVersion::Draft6 | SchemaVersion::Draft7 => &[],
+        SchemaVersion::Draft2019_09 => match key {
+            "additionalItems"
+            | "unevaluatedItems"
+            | "items"
+            | "contains"
+            | "additionalProperties"
+            | "unevaluatedProperties"
+            | "properties"
+            | "patternProperties"
+            | "dependentSchemas"
+            | "propertyNames"
+            | "if"
+            | "then"
+            | "else"
+            | "allOf"
+            | "anyOf"
+            | "oneOf"
+            | "not" => &["https://json-schema.org/draft/2019-09/vocab/applicator"],
+            "multipleOf" | "maximum" | "exclusiveMaximum" | "minimum" | "exclusiveMinimum"
+            | "maxLength" | "minLength" | "pattern" | "maxItems" | "minItems" | "uniqueItems"
+            | "maxContains" | "minContains" | "maxProperties" | "minProperties" | "required"
+            | "dependentRequired" | "const" | "enum" | "type" => {
+                &["https://json-schema.org/draft/2019-09/vocab/validation"]
+            }
+            "format" => &["https://json-schema.org/draft/2019-09/vocab/format"],
+            "contentMediaType" | "contentEncoding" | "contentSchema" => {
+                &["https://json-schema.org/draft/2019-09/vocab/content"]
+            }
+            "title" | "description" | "default" | "deprecated" | "readOnly" | "writeOnly"
+            | "examples" => &["https://json-schema.org/draft/2019-09/vocab/meta-data"],
+            _ => &[],
+        },
+        SchemaVersion::Draft2020_12 => match key {
+            "prefixItems"
+            | "items"
+            | "contains"
+            | "additionalProperties"
+            | "properties"
+            | "patternProperties"
+            | "dependentSchemas"
+            | "propertyNames"
+            | "if"
+            | "then"
+            | "else"
+            | "allOf"
+            | "anyOf"
+            | "oneOf"
+            | "not" => &["https://json-schema.org/draft/2020-12/vocab/applicator"],
+            "unevaluatedItems" | "unevaluatedProperties" => {
+                &["https://json-schema.org/draft/2020-12/vocab/unevaluated"]
+            }
+            "multipleOf" | "maximum" | "exclusiveMaximum" | "minimum" | "exclusiveMinimum"
+            | "maxLength" | "minLength" | "pattern" | "maxItems" | "minItems" | "uniqueItems"
+            | "maxContains" | "minContains" | "maxProperties" | "minProperties" | "required"
+            | "dependentRequired" | "const" | "enum" | "type" => {
+                &["https://json-schema.org/draft/2020-12/vocab/validation"]
+            }
+            "format" => &[
+                "https://json-schema.org/draft/2020-12/vocab/format-annotation",
+                "https://json-schema.org/draft/2020-12/vocab/format-assertion",
+            ],
+            "contentMediaType" | "contentEncoding" | "contentSchema" => {
+                &["https://json-schema.org/draft/2020-12/vocab/content"]
+            }
+            "title" | "description" | "default" | "deprecated" | "readOnly" | "writeOnly"
+            | "examples" => &["https://json-schema.org/draft/2020-12/vocab/meta-data"],
+            _ => &[],
+        },
+    }
+}
+
 #[derive(Debug)]
 pub struct KeywordConsumer {
     pub keys: Vec<&'static str>,
@@ -10,10 +10,9 @@ impl super::Keyword for MultipleOf {
         let multiple_of = keyword_key_exists!(def, "multipleOf");
 
         if multiple_of.is_number() {
-            let multiple_of = multiple_of.as_f64().unwrap();
-            if multiple_of > 0f64 {
+            if multiple_of.as_f64().unwrap() > 0f64 {
                 Ok(Some(Box::new(validators::MultipleOf {
-                    number: multiple_of,
+                    number: multiple_of.as_number().unwrap().clone(),
                 })))
             } else {
                 Err(schema::SchemaError::Malformed {
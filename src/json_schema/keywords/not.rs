@@ -15,7 +15,8 @@ impl super::Keyword for Not {
                 url: helpers::alter_fragment_path(
                     ctx.url.clone(),
                     [ctx.escaped_fragment().as_ref(), "not"].join("/"),
-                ),
+                )
+                .into(),
             })))
         } else {
             Err(schema::SchemaError::Malformed {
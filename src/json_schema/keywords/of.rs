@@ -1,11 +1,12 @@
 use serde_json::Value;
+use url::Url;
 
 use super::super::helpers;
 use super::super::schema;
 use super::super::validators;
 
 macro_rules! of_keyword {
-    ($name:ident, $kw:expr) => {
+    ($name:ident, $kw:expr $(, $field:ident)?) => {
         #[allow(missing_copy_implementations)]
         pub struct $name;
         impl super::Keyword for $name {
@@ -25,15 +26,18 @@ macro_rules! of_keyword {
                     let mut schemes = vec![];
                     for (idx, scheme) in of.iter().enumerate() {
                         if scheme.is_object() || scheme.is_boolean() {
-                            schemes.push(helpers::alter_fragment_path(
-                                ctx.url.clone(),
-                                [
-                                    ctx.escaped_fragment().as_ref(),
-                                    $kw,
-                                    idx.to_string().as_ref(),
-                                ]
-                                .join("/"),
-                            ))
+                            schemes.push(
+                                helpers::alter_fragment_path(
+                                    ctx.url.clone(),
+                                    [
+                                        ctx.escaped_fragment().as_ref(),
+                                        $kw,
+                                        idx.to_string().as_ref(),
+                                    ]
+                                    .join("/"),
+                                )
+                                .into(),
+                            )
                         } else {
                             return Err(schema::SchemaError::Malformed {
                                 path: ctx.fragment.join("/"),
@@ -43,7 +47,10 @@ macro_rules! of_keyword {
                         }
                     }
 
-                    Ok(Some(Box::new(validators::$name { schemes })))
+                    Ok(Some(Box::new(validators::$name {
+                        schemes,
+                        $($field: $field(def, ctx)?, selector: None,)?
+                    })))
                 } else {
                     Err(schema::SchemaError::Malformed {
                         path: ctx.fragment.join("/"),
@@ -56,8 +63,53 @@ macro_rules! of_keyword {
 }
 
 of_keyword!(AllOf, "allOf");
-of_keyword!(AnyOf, "anyOf");
-of_keyword!(OneOf, "oneOf");
+of_keyword!(AnyOf, "anyOf", discriminator);
+of_keyword!(OneOf, "oneOf", discriminator);
+
+/// Compiles the OpenAPI `discriminator` next to `anyOf` and `oneOf`.
+fn discriminator(
+    def: &Value,
+    ctx: &schema::WalkContext<'_>,
+) -> Result<Option<validators::Discriminator>, schema::SchemaError> {
+    let discriminator = match def.get("discriminator") {
+        Some(discriminator) => discriminator,
+        None => return Ok(None),
+    };
+    let malformed = |detail: &str| schema::SchemaError::Malformed {
+        path: ctx.fragment.join("/"),
+        detail: detail.to_string(),
+    };
+
+    let property_name = discriminator
+        .get("propertyName")
+        .and_then(Value::as_str)
+        .ok_or_else(|| {
+            malformed("The value of discriminator MUST be an object with a string propertyName.")
+        })?;
+    let mut mapping = vec![];
+    match discriminator.get("mapping") {
+        Some(Value::Object(entries)) => {
+            for (value, reference) in entries.iter() {
+                let url = reference
+                    .as_str()
+                    .and_then(|reference| {
+                        Url::options().base_url(Some(ctx.url)).parse(reference).ok()
+                    })
+                    .ok_or_else(|| {
+                        malformed("The values of the discriminator mapping MUST be URI references.")
+                    })?;
+                mapping.push((value.clone(), url.into()));
+            }
+        }
+        Some(_) => return Err(malformed("The discriminator mapping MUST be an object.")),
+        None => {}
+    }
+
+    Ok(Some(validators::Discriminator {
+        property_name: property_name.to_string(),
+        mapping,
+    }))
+}
 
 #[cfg(test)]
 use super::super::builder;
@@ -138,6 +190,7 @@ fn conflicting_defaults() {
     let schema = scope
         .compile_and_return(
             json!({
+                "$id": "http://example.com/defaults",
                 "allOf": [
                     {
                         "properties": {
@@ -157,7 +210,7 @@ fn conflicting_defaults() {
     let result = schema.validate(&json!({}));
     assert!(!result.is_valid());
     assert_eq!(&*format!("{result:?}"),
-      "ValidationState { errors: [WrongType { path: \"/a\", detail: \"The value must be number\" }], missing: [], replacement: None, evaluated: {\"/a\"} }");
+      "ValidationState { errors: [WrongType { path: \"/a\", location: ErrorLocation { keyword_location: Some(\"/allOf/0/properties/a/type\"), schema_path: Some(\"/allOf/0/properties/a/type\"), schema_url: Some(\"http://example.com/defaults#/allOf/0/properties/a/type\") }, detail: \"The value must be number\", expected: [Number], actual: Some(String) }], missing: [], replacement: None, evaluated: {\"/a\"}, annotations: {} }");
 }
 
 #[test]
@@ -166,6 +219,7 @@ fn divergent_defaults() {
     let schema = scope
         .compile_and_return(
             json!({
+                "$id": "http://example.com/defaults",
                 "allOf": [
                     {
                         "properties": {
@@ -192,7 +246,7 @@ fn divergent_defaults() {
     assert!(!result.is_valid());
     result.evaluated.clear();
     assert_eq!(&*format!("{result:?}"),
-      "ValidationState { errors: [DivergentDefaults { path: \"\" }], missing: [], replacement: None, evaluated: {} }");
+      "ValidationState { errors: [DivergentDefaults { path: \"\", location: ErrorLocation { keyword_location: Some(\"/allOf\"), schema_path: Some(\"/allOf\"), schema_url: Some(\"http://example.com/defaults#/allOf\") } }], missing: [], replacement: None, evaluated: {}, annotations: {} }");
 }
 
 #[test]
@@ -272,3 +326,197 @@ fn validate_one_of() {
     assert_eq!(schema.validate(&to_value(6).unwrap()).is_valid(), true);
     assert_eq!(schema.validate(&to_value(11).unwrap()).is_valid(), false);
 }
+
+#[cfg(test)]
+fn mk_union() -> Value {
+    json!({
+        "properties": {
+            "shape": {
+                "oneOf": [
+                    {
+                        "properties": {
+                            "kind": { "const": "circle" },
+                            "radius": { "type": "number" }
+                        }
+                    },
+                    {
+                        "properties": {
+                            "kind": { "const": "square" },
+                            "side": { "type": "number", "minimum": 0 }
+                        }
+                    },
+                    {
+                        "properties": {
+                            "kind": { "const": "polygon" },
+                            "sides": { "items": { "type": "number" } }
+                        }
+                    }
+                ]
+            },
+            "tags": { "anyOf": [{ "type": "string" }, { "type": "array" }] }
+        }
+    })
+}
+
+#[test]
+fn best_error() {
+    let mut scope = scope::Scope::new();
+    let schema = scope.compile_and_return(mk_union(), false).unwrap();
+
+    let state = schema.validate(&json!({ "shape": { "kind": "square", "side": -1 } }));
+    let error = state.best_error().unwrap();
+    assert_eq!(error.get_code(), "minimum");
+    assert_eq!(error.get_path(), "/shape/side");
+
+    let state = schema.validate(&json!({ "shape": { "kind": "polygon", "sides": [1, "2"] } }));
+    assert_eq!(state.best_error().unwrap().get_path(), "/shape/sides/1");
+
+    // the branches match the same, so the first one is picked
+    let state = schema.validate(&json!({ "tags": 1 }));
+    let error = state.best_error().unwrap();
+    assert_eq!(error.get_code(), "wrong_type");
+    assert_eq!(
+        error.get_keyword_location(),
+        Some("/properties/tags/anyOf/0/type")
+    );
+
+    let state = schema.validate(&json!({ "shape": {} }));
+    let error = state.best_error().unwrap();
+    assert_eq!(error.get_code(), "one_of");
+    assert_eq!(error.get_path(), "/shape");
+
+    assert!(schema.validate(&json!({})).best_error().is_none());
+}
+
+#[test]
+fn collapse_applicator_errors() {
+    let mut scope = scope::Scope::new().collapse_applicator_errors();
+    let schema = scope.compile_and_return(mk_union(), false).unwrap();
+
+    let state = schema.validate(&json!({ "shape": { "kind": "square", "side": "1" } }));
+    let one_of = state.errors[0]
+        .downcast_ref::<super::super::errors::OneOf>()
+        .unwrap();
+    assert_eq!(one_of.states.len(), 1);
+    assert_eq!(one_of.states[0].errors.len(), 1);
+    assert_eq!(one_of.states[0].errors[0].get_path(), "/shape/side");
+}
+
+#[test]
+fn validate_discriminator() {
+    let mut scope = scope::Scope::new();
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "definitions": {
+                    "Cat": { "properties": { "lives": { "type": "integer" } } },
+                    "Dog": { "properties": { "bark": { "type": "string" } } },
+                    "Bird": { "properties": { "wings": { "type": "integer" } } }
+                },
+                "oneOf": [
+                    { "$ref": "#/definitions/Cat" },
+                    { "$ref": "#/definitions/Dog" },
+                    { "$ref": "#/definitions/Bird" }
+                ],
+                "discriminator": {
+                    "propertyName": "pet",
+                    "mapping": { "parrot": "#/definitions/Bird" }
+                }
+            }),
+            true,
+        )
+        .unwrap();
+
+    // without the discriminator the value would match all branches
+    assert!(schema
+        .validate(&json!({ "pet": "Cat", "lives": 9 }))
+        .is_valid());
+    assert!(schema.validate(&json!({ "pet": "parrot" })).is_valid());
+
+    let state = schema.validate(&json!({ "pet": "Dog", "bark": 1 }));
+    let one_of = state.errors[0]
+        .downcast_ref::<super::super::errors::OneOf>()
+        .unwrap();
+    assert_eq!(one_of.states.len(), 1);
+    assert_eq!(one_of.states[0].errors[0].get_path(), "/bark");
+
+    let state = schema.validate(&json!({ "pet": "Fish" }));
+    let error = state.errors[0]
+        .downcast_ref::<super::super::errors::UnknownDiscriminator>()
+        .unwrap();
+    assert_eq!(error.path, "/pet");
+    assert_eq!(
+        error.allowed,
+        vec![json!("parrot"), json!("Cat"), json!("Dog"), json!("Bird")]
+    );
+    assert_eq!(error.location.keyword_location.as_deref(), Some("/oneOf"));
+}
+
+#[test]
+fn detected_discriminator() {
+    let mut scope = scope::Scope::new().detect_discriminators();
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "definitions": {
+                    "created": {
+                        "properties": {
+                            "type": { "const": "created" },
+                            "id": { "type": "integer" }
+                        }
+                    }
+                },
+                "anyOf": [
+                    { "$ref": "#/definitions/created" },
+                    {
+                        "properties": {
+                            "type": { "enum": ["deleted", "purged"] },
+                            "id": { "type": "string" }
+                        }
+                    }
+                ]
+            }),
+            true,
+        )
+        .unwrap();
+
+    assert!(schema
+        .validate(&json!({ "type": "purged", "id": "a" }))
+        .is_valid());
+    assert!(schema.validate(&json!({ "id": 1 })).is_valid());
+
+    let state = schema.validate(&json!({ "type": "created", "id": "a" }));
+    let any_of = state.errors[0]
+        .downcast_ref::<super::super::errors::AnyOf>()
+        .unwrap();
+    assert_eq!(any_of.states.len(), 1);
+
+    let state = schema.validate(&json!({ "type": "updated" }));
+    assert_eq!(state.errors[0].get_code(), "discriminator");
+    assert_eq!(
+        to_value(&state.errors).unwrap()[0]["allowed"],
+        json!(["created", "deleted", "purged"])
+    );
+}
+
+#[test]
+fn undetected_discriminator() {
+    let def = json!({
+        "anyOf": [
+            { "properties": { "a/b": { "const": 1 } } },
+            { "properties": { "a/b": { "const": 2 } } }
+        ]
+    });
+    let mut scope = scope::Scope::new();
+    let schema = scope.compile_and_return(def.clone(), true).unwrap();
+
+    // without `detect_discriminators` every branch is validated
+    let state = schema.validate(&json!({ "a/b": 3 }));
+    assert_eq!(state.errors[0].get_code(), "any_of");
+
+    let mut scope = scope::Scope::new().detect_discriminators();
+    let schema = scope.compile_and_return(def, true).unwrap();
+    let state = schema.validate(&json!({ "a/b": 3 }));
+    assert_eq!(state.errors[0].get_code(), "discriminator");
+    assert_eq!(state.errors[0].get_path(), "/a/b");
+}
@@ -32,7 +32,8 @@ impl super::Keyword for Properties {
                                     helpers::encode(key).as_ref(),
                                 ]
                                 .join("/"),
-                            ),
+                            )
+                            .into(),
                         );
                     } else {
                         return Err(schema::SchemaError::Malformed {
@@ -63,10 +64,13 @@ impl super::Keyword for Properties {
             if additional_val.is_boolean() {
                 validators::properties::AdditionalKind::Boolean(additional_val.as_bool().unwrap())
             } else if additional_val.is_object() {
-                validators::properties::AdditionalKind::Schema(helpers::alter_fragment_path(
-                    ctx.url.clone(),
-                    [ctx.escaped_fragment().as_ref(), "additionalProperties"].join("/"),
-                ))
+                validators::properties::AdditionalKind::Schema(
+                    helpers::alter_fragment_path(
+                        ctx.url.clone(),
+                        [ctx.escaped_fragment().as_ref(), "additionalProperties"].join("/"),
+                    )
+                    .into(),
+                )
             } else {
                 return Err(schema::SchemaError::Malformed {
                     path: ctx.fragment.join("/"),
@@ -91,7 +95,7 @@ impl super::Keyword for Properties {
                                     ctx.escaped_fragment().as_ref(),
                                     "patternProperties",
                                     helpers::encode(key).as_ref()
-                                ].join("/"));
+                                ].join("/")).into();
                                 patterns.push((regex, url));
                             },
                             Err(_) => {
@@ -121,9 +125,14 @@ impl super::Keyword for Properties {
             vec![]
         };
 
+        let mut known: Vec<String> = properties.keys().cloned().collect();
+        known.sort();
+
         Ok(Some(Box::new(validators::Properties {
             properties,
+            known: known.into(),
             additional: additional_properties,
+            additional_location: ctx.schema_location("additionalProperties"),
             patterns,
         })))
     }
@@ -3,11 +3,16 @@ use serde_json::Value;
 use super::super::helpers;
 use super::super::schema;
 use super::super::validators;
+use super::super::SchemaVersion;
 
 #[allow(missing_copy_implementations)]
 pub struct PropertyNames;
 impl super::Keyword for PropertyNames {
     fn compile(&self, def: &Value, ctx: &schema::WalkContext<'_>) -> super::KeywordResult {
+        if ctx.version < SchemaVersion::Draft6 {
+            return Ok(None);
+        }
+
         let property_names = keyword_key_exists!(def, "propertyNames");
 
         if property_names.is_object() || property_names.is_boolean() {
@@ -15,7 +20,8 @@ impl super::Keyword for PropertyNames {
                 url: helpers::alter_fragment_path(
                     ctx.url.clone(),
                     [ctx.escaped_fragment().as_ref(), "propertyNames"].join("/"),
-                ),
+                )
+                .into(),
             })))
         } else {
             Err(schema::SchemaError::Malformed {
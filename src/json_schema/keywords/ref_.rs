@@ -17,7 +17,7 @@ impl super::Keyword for Ref {
                 .base_url(Some(ctx.url))
                 .parse(ref_.as_str().unwrap());
             match url {
-                Ok(url) => Ok(Some(Box::new(validators::Ref { url }))),
+                Ok(url) => Ok(Some(Box::new(validators::Ref { url: url.into() }))),
                 Err(_) => Err(schema::SchemaError::Malformed {
                     path: ctx.fragment.join("/"),
                     detail: "The value of $ref MUST be an URI-encoded JSON Pointer".to_string(),
@@ -36,6 +36,76 @@ impl super::Keyword for Ref {
     }
 }
 
+#[allow(missing_copy_implementations)]
+pub struct RecursiveRef;
+impl super::Keyword for RecursiveRef {
+    fn compile(&self, def: &Value, ctx: &schema::WalkContext<'_>) -> super::KeywordResult {
+        if ctx.version != SchemaVersion::Draft2019_09 {
+            return Ok(None);
+        }
+
+        let ref_ = keyword_key_exists!(def, "$recursiveRef");
+
+        if ref_.is_string() {
+            let url = Url::options()
+                .base_url(Some(ctx.url))
+                .parse(ref_.as_str().unwrap());
+            match url {
+                Ok(url) => Ok(Some(Box::new(validators::RecursiveRef { url: url.into() }))),
+                Err(_) => Err(schema::SchemaError::Malformed {
+                    path: ctx.fragment.join("/"),
+                    detail: "The value of $recursiveRef MUST be an URI reference".to_string(),
+                }),
+            }
+        } else {
+            Err(schema::SchemaError::Malformed {
+                path: ctx.fragment.join("/"),
+                detail: "The value of $recursiveRef MUST be a string".to_string(),
+            })
+        }
+    }
+}
+
+#[allow(missing_copy_implementations)]
+pub struct DynamicRef;
+impl super::Keyword for DynamicRef {
+    fn compile(&self, def: &Value, ctx: &schema::WalkContext<'_>) -> super::KeywordResult {
+        if ctx.version < SchemaVersion::Draft2020_12 {
+            return Ok(None);
+        }
+
+        let ref_ = keyword_key_exists!(def, "$dynamicRef");
+
+        if ref_.is_string() {
+            let url = Url::options()
+                .base_url(Some(ctx.url))
+                .parse(ref_.as_str().unwrap());
+            match url {
+                Ok(url) => {
+                    // only plain-name fragments may refer to a $dynamicAnchor
+                    let anchor = url
+                        .fragment()
+                        .filter(|f| !f.is_empty() && !f.starts_with('/'))
+                        .map(|f| f.to_string());
+                    Ok(Some(Box::new(validators::DynamicRef {
+                        url: url.into(),
+                        anchor,
+                    })))
+                }
+                Err(_) => Err(schema::SchemaError::Malformed {
+                    path: ctx.fragment.join("/"),
+                    detail: "The value of $dynamicRef MUST be an URI reference".to_string(),
+                }),
+            }
+        } else {
+            Err(schema::SchemaError::Malformed {
+                path: ctx.fragment.join("/"),
+                detail: "The value of $dynamicRef MUST be a string".to_string(),
+            })
+        }
+    }
+}
+
 #[cfg(test)]
 use super::super::builder;
 #[cfg(test)]
@@ -121,3 +191,189 @@ fn validate() {
         false
     );
 }
+
+#[test]
+fn validate_dynamic_ref() {
+    let mut scope = scope::Scope::new().set_version(SchemaVersion::Draft2020_12);
+    scope
+        .compile(
+            json!({
+                "$id": "https://example.com/tree",
+                "$dynamicAnchor": "node",
+                "type": "object",
+                "properties": {
+                    "data": true,
+                    "children": {
+                        "type": "array",
+                        "items": { "$dynamicRef": "#node" }
+                    }
+                }
+            }),
+            false,
+        )
+        .unwrap();
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "$id": "https://example.com/strict-tree",
+                "$dynamicAnchor": "node",
+                "$ref": "tree",
+                "unevaluatedProperties": false
+            }),
+            false,
+        )
+        .unwrap();
+
+    assert!(schema
+        .validate(&json!({"children": [{"data": 1}]}))
+        .is_valid());
+    // the misspelled property is caught because "#node" resolves to the strict tree
+    assert!(!schema
+        .validate(&json!({"children": [{"daat": 1}]}))
+        .is_valid());
+}
+
+#[test]
+fn validate_dynamic_ref_without_dynamic_anchor() {
+    let mut scope = scope::Scope::new().set_version(SchemaVersion::Draft2020_12);
+    scope
+        .compile(
+            json!({
+                "$id": "https://example.com/list",
+                "type": "array",
+                "items": { "$dynamicRef": "#items" },
+                "$defs": {
+                    "items": { "$anchor": "items", "type": "string" }
+                }
+            }),
+            false,
+        )
+        .unwrap();
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "$id": "https://example.com/numbers",
+                "$ref": "list",
+                "$defs": {
+                    "items": { "$dynamicAnchor": "items", "type": "integer" }
+                }
+            }),
+            false,
+        )
+        .unwrap();
+
+    // "#items" is a plain $anchor in the list schema, so it is not looked up dynamically
+    assert!(schema.validate(&json!(["a", "b"])).is_valid());
+    assert!(!schema.validate(&json!([1])).is_valid());
+}
+
+#[test]
+fn resolve_nested_resources() {
+    let mut scope = scope::Scope::new().set_version(SchemaVersion::Draft2019_09);
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "$id": "https://example.com/root",
+                "properties": {
+                    "a": { "$ref": "nested#/$defs/positive" },
+                    "b": { "$ref": "#anchored" }
+                },
+                "$defs": {
+                    "nested": {
+                        "$id": "nested",
+                        "$defs": {
+                            "positive": { "type": "integer", "minimum": 1 }
+                        }
+                    },
+                    "anchored": {
+                        "$anchor": "anchored",
+                        "items": { "type": "string" }
+                    }
+                }
+            }),
+            false,
+        )
+        .unwrap();
+
+    assert!(schema.validate(&json!({"a": 1, "b": ["x"]})).is_valid());
+    assert!(!schema.validate(&json!({"a": 0})).is_valid());
+    assert!(!schema.validate(&json!({"b": [1]})).is_valid());
+}
+
+#[test]
+fn validate_recursive_ref() {
+    let mut scope = scope::Scope::new().set_version(SchemaVersion::Draft2019_09);
+    scope
+        .compile(
+            json!({
+                "$id": "https://example.com/tree",
+                "$recursiveAnchor": true,
+                "type": "object",
+                "properties": {
+                    "data": true,
+                    "children": {
+                        "type": "array",
+                        "items": { "$recursiveRef": "#" }
+                    }
+                }
+            }),
+            false,
+        )
+        .unwrap();
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "$id": "https://example.com/strict-tree",
+                "$recursiveAnchor": true,
+                "$ref": "tree",
+                "unevaluatedProperties": false
+            }),
+            false,
+        )
+        .unwrap();
+
+    assert!(schema
+        .validate(&json!({"children": [{"data": 1}]}))
+        .is_valid());
+    // the misspelled property is caught because "#" resolves to the strict tree
+    assert!(!schema
+        .validate(&json!({"children": [{"daat": 1}]}))
+        .is_valid());
+}
+
+#[test]
+fn validate_recursive_ref_without_recursive_anchor() {
+    let mut scope = scope::Scope::new().set_version(SchemaVersion::Draft2019_09);
+    scope
+        .compile(
+            json!({
+                "$id": "https://example.com/tree",
+                "type": "object",
+                "properties": {
+                    "data": true,
+                    "children": {
+                        "type": "array",
+                        "items": { "$recursiveRef": "#" }
+                    }
+                }
+            }),
+            false,
+        )
+        .unwrap();
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "$id": "https://example.com/strict-tree",
+                "$recursiveAnchor": true,
+                "$ref": "tree",
+                "unevaluatedProperties": false
+            }),
+            false,
+        )
+        .unwrap();
+
+    // without "$recursiveAnchor" in the tree, "#" stays within the tree
+    assert!(schema
+        .validate(&json!({"children": [{"daat": 1}]}))
+        .is_valid());
+}
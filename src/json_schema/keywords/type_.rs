@@ -2,6 +2,7 @@ use serde_json::Value;
 
 use super::super::schema;
 use super::super::validators;
+use super::super::SchemaVersion;
 
 #[allow(missing_copy_implementations)]
 pub struct Type;
@@ -15,6 +16,7 @@ impl super::Keyword for Type {
             if let Some(ty) = ty {
                 Ok(Some(Box::new(validators::Type {
                     item: validators::type_::TypeKind::Single(ty),
+                    integral_floats: ctx.version > SchemaVersion::Draft4,
                 })))
             } else {
                 Err(schema::SchemaError::Malformed {
@@ -57,6 +59,7 @@ impl super::Keyword for Type {
 
             Ok(Some(Box::new(validators::Type {
                 item: validators::type_::TypeKind::Set(converted_types),
+                integral_floats: ctx.version > SchemaVersion::Draft4,
             })))
         } else {
             Err(schema::SchemaError::Malformed {
@@ -332,3 +335,33 @@ fn malformed() {
         )
         .is_err());
 }
+
+#[test]
+fn wrong_type_error_fields() {
+    let mut scope = scope::Scope::new();
+    let schema = scope
+        .compile_and_return(json!({ "type": ["integer", "null"] }), true)
+        .unwrap();
+
+    let state = schema.validate(&json!(1.5));
+    let error = to_value(&state.errors).unwrap();
+    assert_eq!(error[0]["expected"], json!(["integer", "null"]));
+    assert_eq!(error[0]["actual"], json!("number"));
+}
+
+#[test]
+fn validate_integer_draft4() {
+    let mut scope = scope::Scope::new().set_version(SchemaVersion::Draft4);
+    let schema = scope
+        .compile_and_return(json!({ "type": "integer" }), true)
+        .unwrap();
+
+    assert!(schema.validate(&json!(1)).is_valid());
+    assert!(!schema.validate(&json!(1.0)).is_valid());
+
+    let mut scope = scope::Scope::new();
+    let schema = scope
+        .compile_and_return(json!({ "type": "integer" }), true)
+        .unwrap();
+    assert!(schema.validate(&json!(1.0)).is_valid());
+}
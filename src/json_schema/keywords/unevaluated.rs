@@ -28,7 +28,8 @@ impl Keyword for UnevaluatedItems {
                     helpers::alter_fragment_path(
                         ctx.url.clone(),
                         [ctx.escaped_fragment().as_ref(), "unevaluatedItems"].join("/"),
-                    ),
+                    )
+                    .into(),
                 ),
             },
             _ => {
@@ -70,7 +71,8 @@ impl Keyword for UnevaluatedProperties {
                     helpers::alter_fragment_path(
                         ctx.url.clone(),
                         [ctx.escaped_fragment().as_ref(), "unevaluatedProperties"].join("/"),
-                    ),
+                    )
+                    .into(),
                 ),
             },
             _ => {
@@ -0,0 +1,431 @@
+//! Detection of constraints that contradict each other or have no effect, see
+//! `Scope::lint`.
+
+use serde_json::{Map, Value};
+use url::Url;
+
+use super::keywords::KeywordMap;
+use super::schema::{Schema, ScopedSchema};
+use super::scope::Scope;
+use super::validators::ValidationState;
+use super::{PrimitiveType, SchemaVersion};
+
+/// A mistake in a schema that does not prevent it from compiling.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Warning {
+    /// The id of the compiled document that contains the schema.
+    pub document: Url,
+    /// The JSON pointer of the offending keyword within the document.
+    pub pointer: String,
+    /// Identifies the kind of mistake, e.g. `contradictory_bounds`.
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// Validates the `default` and each entry of `examples` of a schema against the schema
+/// itself, and returns the JSON pointer (relative to the schema) and the validation result
+/// of each value that does not match.
+pub(crate) fn invalid_examples(scope: &Scope, schema: &Schema) -> Vec<(String, ValidationState)> {
+    let mut values = vec![];
+    if let Some(default) = schema.original.get("default") {
+        values.push(("/default".to_string(), default));
+    }
+    if let Some(Value::Array(examples)) = schema.original.get("examples") {
+        for (idx, example) in examples.iter().enumerate() {
+            values.push((format!("/examples/{idx}"), example));
+        }
+    }
+    values
+        .into_iter()
+        .map(|(pointer, value)| (pointer, ScopedSchema::new(scope, schema).validate(value)))
+        .filter(|(_, state)| !state.is_valid())
+        .collect()
+}
+
+/// The keywords that only constrain instances of one type.
+const TYPED_KEYWORDS: &[(&str, PrimitiveType)] = &[
+    ("minLength", PrimitiveType::String),
+    ("maxLength", PrimitiveType::String),
+    ("pattern", PrimitiveType::String),
+    ("contentMediaType", PrimitiveType::String),
+    ("contentEncoding", PrimitiveType::String),
+    ("minimum", PrimitiveType::Number),
+    ("maximum", PrimitiveType::Number),
+    ("exclusiveMinimum", PrimitiveType::Number),
+    ("exclusiveMaximum", PrimitiveType::Number),
+    ("multipleOf", PrimitiveType::Number),
+    ("items", PrimitiveType::Array),
+    ("additionalItems", PrimitiveType::Array),
+    ("prefixItems", PrimitiveType::Array),
+    ("contains", PrimitiveType::Array),
+    ("minItems", PrimitiveType::Array),
+    ("maxItems", PrimitiveType::Array),
+    ("uniqueItems", PrimitiveType::Array),
+    ("unevaluatedItems", PrimitiveType::Array),
+    ("properties", PrimitiveType::Object),
+    ("patternProperties", PrimitiveType::Object),
+    ("additionalProperties", PrimitiveType::Object),
+    ("required", PrimitiveType::Object),
+    ("minProperties", PrimitiveType::Object),
+    ("maxProperties", PrimitiveType::Object),
+    ("propertyNames", PrimitiveType::Object),
+    ("dependencies", PrimitiveType::Object),
+    ("dependentRequired", PrimitiveType::Object),
+    ("dependentSchemas", PrimitiveType::Object),
+    ("unevaluatedProperties", PrimitiveType::Object),
+];
+
+/// The pairs of keywords that bound a count from below and above.
+const COUNT_BOUNDS: &[(&str, &str)] = &[
+    ("minLength", "maxLength"),
+    ("minItems", "maxItems"),
+    ("minProperties", "maxProperties"),
+    ("minContains", "maxContains"),
+];
+
+struct Linter<'s> {
+    scope: &'s Scope,
+    keywords: &'s KeywordMap,
+    document: &'s Url,
+    warnings: Vec<Warning>,
+}
+
+/// Lints a compiled document and all of its subschemas.
+pub(crate) fn lint_document(
+    scope: &Scope,
+    keywords: &KeywordMap,
+    document: &Url,
+    schema: &Schema,
+) -> Vec<Warning> {
+    let mut linter = Linter {
+        scope,
+        keywords,
+        document,
+        warnings: vec![],
+    };
+    linter.lint(schema);
+    linter.warnings
+}
+
+impl<'s> Linter<'s> {
+    fn lint(&mut self, schema: &Schema) {
+        if schema.is_schema {
+            if let Value::Object(ref object) = schema.original {
+                self.lint_schema(schema, object);
+            }
+        }
+        for subschema in schema.tree.values() {
+            self.lint(subschema);
+        }
+    }
+
+    fn warn(&mut self, schema: &Schema, keyword: &str, code: &'static str, message: String) {
+        let escaped = keyword.replace('~', "~0").replace('/', "~1");
+        self.warn_at(format!("{}/{}", schema.pointer, escaped), code, message);
+    }
+
+    fn warn_at(&mut self, pointer: String, code: &'static str, message: String) {
+        self.warnings.push(Warning {
+            document: self.document.clone(),
+            pointer,
+            code,
+            message,
+        });
+    }
+
+    fn lint_schema(&mut self, schema: &Schema, object: &Map<String, Value>) {
+        // the other keywords are ignored next to an exclusive `$ref`
+        if let Some(reference) = self.keywords.get("$ref") {
+            if object.contains_key("$ref") && reference.keyword.is_exclusive(schema.version) {
+                for key in object.keys() {
+                    if key != "$ref" && self.keywords.contains_key(key.as_str()) {
+                        self.warn(
+                            schema,
+                            key,
+                            "ignored_keyword",
+                            format!("`{key}` is ignored next to `$ref`"),
+                        );
+                    }
+                }
+                return;
+            }
+        }
+
+        self.lint_numeric_bounds(schema, object);
+        for (min, max) in COUNT_BOUNDS.iter() {
+            if let (Some(low), Some(high)) = (
+                object.get(*min).and_then(Value::as_f64),
+                object.get(*max).and_then(Value::as_f64),
+            ) {
+                if low > high {
+                    self.warn(
+                        schema,
+                        min,
+                        "contradictory_bounds",
+                        format!("`{min}` {low} is greater than `{max}` {high}"),
+                    );
+                }
+            }
+        }
+        self.lint_types(schema, object);
+        self.lint_required(schema, object);
+        self.lint_companions(schema, object);
+
+        for (pointer, state) in invalid_examples(self.scope, schema) {
+            let reasons: Vec<&str> = state.errors.iter().map(|error| error.get_title()).collect();
+            let (code, name) = if pointer == "/default" {
+                ("invalid_default", "the default")
+            } else {
+                ("invalid_example", "the example")
+            };
+            self.warn_at(
+                format!("{}{}", schema.pointer, pointer),
+                code,
+                format!("{} does not match the schema: {}", name, reasons.join(", ")),
+            );
+        }
+    }
+
+    /// `minimum`/`exclusiveMinimum` against `maximum`/`exclusiveMaximum`, where the
+    /// exclusive keywords are booleans that modify the others in draft 4.
+    fn lint_numeric_bounds(&mut self, schema: &Schema, object: &Map<String, Value>) {
+        let is_true = |key: &str| object.get(key).and_then(Value::as_bool).unwrap_or(false);
+        let bound = |key: &'static str, modifier: &str| {
+            object.get(key).and_then(Value::as_f64).map(|value| {
+                let exclusive = match key {
+                    "exclusiveMinimum" | "exclusiveMaximum" => true,
+                    _ => schema.version == SchemaVersion::Draft4 && is_true(modifier),
+                };
+                (key, value, exclusive)
+            })
+        };
+        let lower = [
+            bound("minimum", "exclusiveMinimum"),
+            bound("exclusiveMinimum", ""),
+        ];
+        let upper = [
+            bound("maximum", "exclusiveMaximum"),
+            bound("exclusiveMaximum", ""),
+        ];
+
+        for (min, low, low_exclusive) in lower.iter().flatten() {
+            for (max, high, high_exclusive) in upper.iter().flatten() {
+                if low > high || (low == high && (*low_exclusive || *high_exclusive)) {
+                    self.warn(
+                        schema,
+                        min,
+                        "contradictory_bounds",
+                        format!("no number satisfies both `{min}` {low} and `{max}` {high}"),
+                    );
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Keywords for one type in a schema that does not allow that type.
+    fn lint_types(&mut self, schema: &Schema, object: &Map<String, Value>) {
+        let types: Vec<&str> = match object.get("type") {
+            Some(Value::String(name)) => vec![name.as_str()],
+            Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+            _ => return,
+        };
+        for (keyword, primitive) in TYPED_KEYWORDS.iter() {
+            if !object.contains_key(*keyword) {
+                continue;
+            }
+            let allowed = types.iter().any(|name| {
+                *name == primitive.to_string()
+                    || (*name == "integer" && matches!(primitive, PrimitiveType::Number))
+            });
+            if !allowed {
+                self.warn(
+                    schema,
+                    keyword,
+                    "inapplicable_keyword",
+                    format!(
+                        "`{}` only applies to values of type {}, which `type` does not allow",
+                        keyword, primitive
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Required properties that `additionalProperties: false` forbids.
+    fn lint_required(&mut self, schema: &Schema, object: &Map<String, Value>) {
+        if object.get("additionalProperties") != Some(&Value::Bool(false)) {
+            return;
+        }
+        let required = match object.get("required") {
+            Some(Value::Array(required)) => required,
+            _ => return,
+        };
+        let properties = object.get("properties").and_then(Value::as_object);
+        let patterns: Vec<fancy_regex::Regex> = object
+            .get("patternProperties")
+            .and_then(Value::as_object)
+            .map(|patterns| {
+                patterns
+                    .keys()
+                    .filter_map(|pattern| fancy_regex::Regex::new(pattern).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        for (idx, name) in required.iter().enumerate() {
+            let name = match name.as_str() {
+                Some(name) => name,
+                None => continue,
+            };
+            let allowed = properties
+                .map(|properties| properties.contains_key(name))
+                .unwrap_or(false)
+                || patterns
+                    .iter()
+                    .any(|pattern| pattern.is_match(name).unwrap_or(false));
+            if !allowed {
+                self.warn_at(
+                    format!("{}/required/{}", schema.pointer, idx),
+                    "unsatisfiable_required",
+                    format!(
+                        "the required property `{name}` is forbidden by `additionalProperties`"
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Keywords that have no effect without the keyword they are compiled with.
+    fn lint_companions(&mut self, schema: &Schema, object: &Map<String, Value>) {
+        for leading in ["if", "contains"].iter() {
+            let consumer = match self.keywords.get(leading) {
+                Some(consumer) => consumer,
+                None => continue,
+            };
+            let companions: Vec<&str> = consumer
+                .keys
+                .iter()
+                .filter(|key| *key != leading && object.contains_key(**key))
+                .cloned()
+                .collect();
+            if object.contains_key(*leading) {
+                if companions.is_empty() && *leading == "if" {
+                    self.warn(
+                        schema,
+                        leading,
+                        "ineffective_keyword",
+                        "`if` has no effect without `then` or `else`".to_string(),
+                    );
+                }
+            } else {
+                for companion in companions {
+                    self.warn(
+                        schema,
+                        companion,
+                        "ineffective_keyword",
+                        format!("`{companion}` has no effect without `{leading}`"),
+                    );
+                }
+            }
+        }
+
+        if schema.version < SchemaVersion::Draft2020_12
+            && object.contains_key("additionalItems")
+            && !object.get("items").map(Value::is_array).unwrap_or(false)
+        {
+            self.warn(
+                schema,
+                "additionalItems",
+                "ineffective_keyword",
+                "`additionalItems` has no effect unless `items` is an array".to_string(),
+            );
+        }
+    }
+}
+
+#[test]
+fn lint_mistakes() {
+    let mut scope = Scope::new();
+    scope
+        .compile_with_id(
+            &Url::parse("http://example.com/lint").unwrap(),
+            json!({
+                "definitions": {
+                    "bounds": {
+                        "minimum": 5,
+                        "exclusiveMaximum": 5,
+                        "minLength": 3,
+                        "maxLength": 2,
+                        "minItems": 1,
+                        "maxItems": 1
+                    },
+                    "types": { "type": ["integer", "null"], "pattern": "^a", "maximum": 3 },
+                    "closed": {
+                        "properties": { "a": true },
+                        "patternProperties": { "^x-": true },
+                        "additionalProperties": false,
+                        "required": ["a", "x-b", "c"]
+                    },
+                    "named": { "type": "string", "default": 1, "examples": ["a", 2] },
+                    "conditional": { "if": { "minimum": 1 }, "maxContains": 1 },
+                    "reference": { "$ref": "#/definitions/types", "minimum": 1, "title": "x" },
+                    "valid": {
+                        "minimum": 1,
+                        "maximum": 1,
+                        "default": 1,
+                        "properties": { "minimum": { "maximum": 0 } }
+                    }
+                }
+            }),
+            false,
+        )
+        .unwrap();
+
+    let warnings: Vec<(String, &str)> = scope
+        .lint()
+        .into_iter()
+        .map(|warning| (warning.pointer, warning.code))
+        .collect();
+    let expected = vec![
+        ("/definitions/bounds/minLength", "contradictory_bounds"),
+        ("/definitions/bounds/minimum", "contradictory_bounds"),
+        ("/definitions/closed/required/2", "unsatisfiable_required"),
+        ("/definitions/conditional/if", "ineffective_keyword"),
+        (
+            "/definitions/conditional/maxContains",
+            "ineffective_keyword",
+        ),
+        ("/definitions/named/default", "invalid_default"),
+        ("/definitions/named/examples/1", "invalid_example"),
+        ("/definitions/reference/minimum", "ignored_keyword"),
+        ("/definitions/types/pattern", "inapplicable_keyword"),
+    ];
+    assert_eq!(
+        warnings,
+        expected
+            .into_iter()
+            .map(|(pointer, code)| (pointer.to_string(), code))
+            .collect::<Vec<_>>()
+    );
+
+    let mut scope = Scope::new().set_version(SchemaVersion::Draft4);
+    scope
+        .compile(
+            json!({
+                "properties": {
+                    "a": { "minimum": 1, "maximum": 1, "exclusiveMaximum": true },
+                    "b": { "minimum": 1, "maximum": 1, "exclusiveMaximum": false }
+                }
+            }),
+            false,
+        )
+        .unwrap();
+    let warnings = scope.lint();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].pointer, "/properties/a/minimum");
+    assert_eq!(
+        warnings[0].message,
+        "no number satisfies both `minimum` 1 and `maximum` 1"
+    );
+}
@@ -0,0 +1,133 @@
+use serde_json::Value;
+use std::sync;
+use url::Url;
+
+use super::resolver;
+use super::schema;
+use super::scope;
+use super::SchemaVersion;
+
+/// The standard metaschemas and the vocabulary metaschemas they are composed of.
+const DOCUMENTS: &[(&str, &str)] = &[
+    (
+        "http://json-schema.org/draft-04/schema",
+        include_str!("metaschemas/draft4.json"),
+    ),
+    (
+        "http://json-schema.org/draft-06/schema",
+        include_str!("metaschemas/draft6.json"),
+    ),
+    (
+        "http://json-schema.org/draft-07/schema",
+        include_str!("metaschemas/draft7.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2019-09/schema",
+        include_str!("metaschemas/draft2019-09/schema.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2019-09/meta/core",
+        include_str!("metaschemas/draft2019-09/meta/core.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2019-09/meta/applicator",
+        include_str!("metaschemas/draft2019-09/meta/applicator.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2019-09/meta/validation",
+        include_str!("metaschemas/draft2019-09/meta/validation.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2019-09/meta/meta-data",
+        include_str!("metaschemas/draft2019-09/meta/meta-data.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2019-09/meta/format",
+        include_str!("metaschemas/draft2019-09/meta/format.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2019-09/meta/content",
+        include_str!("metaschemas/draft2019-09/meta/content.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/schema",
+        include_str!("metaschemas/draft2020-12/schema.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/core",
+        include_str!("metaschemas/draft2020-12/meta/core.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/applicator",
+        include_str!("metaschemas/draft2020-12/meta/applicator.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/unevaluated",
+        include_str!("metaschemas/draft2020-12/meta/unevaluated.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/validation",
+        include_str!("metaschemas/draft2020-12/meta/validation.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/meta-data",
+        include_str!("metaschemas/draft2020-12/meta/meta-data.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/format-annotation",
+        include_str!("metaschemas/draft2020-12/meta/format-annotation.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/format-assertion",
+        include_str!("metaschemas/draft2020-12/meta/format-assertion.json"),
+    ),
+    (
+        "https://json-schema.org/draft/2020-12/meta/content",
+        include_str!("metaschemas/draft2020-12/meta/content.json"),
+    ),
+];
+
+/// Returns the bundled document for the URL (without fragment) of a standard metaschema.
+pub fn document(url: &Url) -> Option<Value> {
+    DOCUMENTS
+        .iter()
+        .find(|(document_url, _)| *document_url == url.as_str())
+        .map(|(_, document)| serde_json::from_str(document).unwrap())
+}
+
+/// Serves the bundled documents, so that the vocabulary metaschemas are loaded through
+/// the references of the standard metaschemas.
+struct Bundled;
+
+impl resolver::SchemaResolver for Bundled {
+    fn resolve(&self, url: &Url) -> Result<Option<Value>, schema::SchemaError> {
+        Ok(document(url))
+    }
+}
+
+/// Compiles the standard metaschemas of all drafts into a new scope.
+fn scope() -> scope::Scope {
+    let mut scope = scope::Scope::new();
+    scope.bundled_metaschemas = false;
+    scope.add_resolver(Bundled);
+    for version in [
+        SchemaVersion::Draft4,
+        SchemaVersion::Draft6,
+        SchemaVersion::Draft7,
+        SchemaVersion::Draft2019_09,
+        SchemaVersion::Draft2020_12,
+    ] {
+        let url = version.metaschema_url();
+        scope
+            .compile_with_id(&url, document(&url).unwrap(), false)
+            .unwrap();
+    }
+    scope
+}
+
+/// The scope with the standard metaschemas of all drafts, which is compiled once and
+/// shared by all scopes that refer to a standard metaschema.
+pub fn shared() -> &'static scope::Scope {
+    static SCOPE: sync::OnceLock<scope::Scope> = sync::OnceLock::new();
+    SCOPE.get_or_init(scope)
+}
@@ -5,13 +5,26 @@ use std::str;
 pub mod helpers;
 #[macro_use]
 pub mod keywords;
+mod best_match;
 pub mod builder;
+mod bundle;
+pub mod compiled;
+mod dereference;
 pub mod errors;
+pub mod lint;
+mod metaschemas;
+pub mod output;
+pub mod report;
+pub mod resolver;
 pub mod schema;
 pub mod scope;
 pub mod validators;
 
 pub use self::builder::{schema, Builder};
+pub use self::compiled::CompiledSchema;
+pub use self::dereference::RecursiveRefs;
+pub use self::output::{OutputFormat, OutputUnit};
+pub use self::resolver::SchemaResolver;
 pub use self::schema::{Schema, SchemaError};
 pub use self::scope::Scope;
 pub use self::validators::ValidationState;
@@ -19,13 +32,51 @@ pub use self::validators::ValidationState;
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Ord, PartialOrd)]
 /// Represents the schema version to use.
 pub enum SchemaVersion {
+    /// Use draft 4.
+    Draft4,
+    /// Use draft 6.
+    Draft6,
     /// Use draft 7.
     Draft7,
     /// Use draft 2019-09.
     Draft2019_09,
+    /// Use draft 2020-12.
+    Draft2020_12,
 }
 
-#[derive(Copy, Debug, Clone)]
+impl SchemaVersion {
+    /// Returns the version of a standard metaschema, as referenced by `$schema`.
+    pub fn from_metaschema_url(url: &url::Url) -> Option<SchemaVersion> {
+        let mut url = url.clone();
+        if url.fragment() == Some("") {
+            url.set_fragment(None);
+        }
+        // the old drafts are commonly referenced both with and without TLS
+        let _ = url.set_scheme("http");
+        match url.as_str() {
+            "http://json-schema.org/draft-04/schema" => Some(SchemaVersion::Draft4),
+            "http://json-schema.org/draft-06/schema" => Some(SchemaVersion::Draft6),
+            "http://json-schema.org/draft-07/schema" => Some(SchemaVersion::Draft7),
+            "http://json-schema.org/draft/2019-09/schema" => Some(SchemaVersion::Draft2019_09),
+            "http://json-schema.org/draft/2020-12/schema" => Some(SchemaVersion::Draft2020_12),
+            _ => None,
+        }
+    }
+
+    /// Returns the URL of the standard metaschema of this version.
+    pub fn metaschema_url(self) -> url::Url {
+        let url = match self {
+            SchemaVersion::Draft4 => "http://json-schema.org/draft-04/schema",
+            SchemaVersion::Draft6 => "http://json-schema.org/draft-06/schema",
+            SchemaVersion::Draft7 => "http://json-schema.org/draft-07/schema",
+            SchemaVersion::Draft2019_09 => "https://json-schema.org/draft/2019-09/schema",
+            SchemaVersion::Draft2020_12 => "https://json-schema.org/draft/2020-12/schema",
+        };
+        url::Url::parse(url).unwrap()
+    }
+}
+
+#[derive(Copy, Debug, Clone, PartialEq, Eq)]
 pub enum PrimitiveType {
     Array,
     Boolean,
@@ -36,6 +87,23 @@ pub enum PrimitiveType {
     String,
 }
 
+impl PrimitiveType {
+    /// The type of a value, `Integer` for numbers that are not stored as floats.
+    pub fn of(value: &serde_json::Value) -> PrimitiveType {
+        match value {
+            serde_json::Value::Array(_) => PrimitiveType::Array,
+            serde_json::Value::Bool(_) => PrimitiveType::Boolean,
+            serde_json::Value::Number(number) if number.is_u64() || number.is_i64() => {
+                PrimitiveType::Integer
+            }
+            serde_json::Value::Number(_) => PrimitiveType::Number,
+            serde_json::Value::Null => PrimitiveType::Null,
+            serde_json::Value::Object(_) => PrimitiveType::Object,
+            serde_json::Value::String(_) => PrimitiveType::String,
+        }
+    }
+}
+
 impl str::FromStr for PrimitiveType {
     type Err = ();
     fn from_str(s: &str) -> Result<PrimitiveType, ()> {
@@ -65,3 +133,12 @@ impl fmt::Display for PrimitiveType {
         })
     }
 }
+
+impl serde::Serialize for PrimitiveType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
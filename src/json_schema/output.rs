@@ -0,0 +1,425 @@
+//! The standardized output formats of JSON Schema validation results.
+//!
+//! See <https://json-schema.org/draft/2019-09/json-schema-core.html#rfc.section.10>.
+
+use serde::{Serialize, Serializer};
+use serde_json::{to_value, Value};
+use std::cell::RefCell;
+use std::mem;
+
+use super::super::common::error::ValicoError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Only tells whether the instance is valid.
+    Flag,
+    /// A flat list of all errors.
+    Basic,
+    /// The errors arranged in a tree that follows the structure of the schema, units with
+    /// a single nested unit are replaced by that unit.
+    Detailed,
+    /// The complete tree of all evaluated schemas and keywords, including valid ones.
+    Verbose,
+}
+
+/// A single output unit.
+///
+/// `errors` holds the nested units, they are serialized as `errors` for invalid units
+/// and as `annotations` for valid ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputUnit {
+    pub valid: bool,
+    pub keyword_location: Option<String>,
+    pub absolute_keyword_location: Option<String>,
+    pub instance_location: Option<String>,
+    pub error: Option<String>,
+    pub errors: Vec<OutputUnit>,
+}
+
+impl OutputUnit {
+    fn new(valid: bool, keyword_location: String, absolute: String, instance: String) -> Self {
+        OutputUnit {
+            valid,
+            keyword_location: Some(keyword_location),
+            absolute_keyword_location: Some(absolute),
+            instance_location: Some(instance),
+            error: None,
+            errors: vec![],
+        }
+    }
+
+    pub(crate) fn flag(valid: bool) -> Self {
+        OutputUnit {
+            valid,
+            keyword_location: None,
+            absolute_keyword_location: None,
+            instance_location: None,
+            error: None,
+            errors: vec![],
+        }
+    }
+
+    /// Takes over the locations of an error.
+    fn locate(&mut self, error: &dyn ValicoError) {
+        self.instance_location = Some(error.get_path().to_string());
+        if let Some(location) = error.get_keyword_location() {
+            self.keyword_location = Some(location.to_string());
+        }
+        if let Some(url) = error.get_schema_url() {
+            self.absolute_keyword_location = Some(url.to_string());
+        }
+    }
+
+    /// Converts the complete (verbose) tree into the given format.
+    pub(crate) fn into_format(self, format: OutputFormat) -> OutputUnit {
+        match format {
+            OutputFormat::Verbose => self,
+            OutputFormat::Detailed => {
+                let mut unit = self.detailed();
+                unit.absolute_keyword_location = None;
+                unit
+            }
+            OutputFormat::Basic => {
+                let valid = self.valid;
+                let mut errors = vec![];
+                if !valid {
+                    self.detailed().collect_errors(&mut errors);
+                }
+                OutputUnit {
+                    errors,
+                    ..OutputUnit::flag(valid)
+                }
+            }
+            OutputFormat::Flag => OutputUnit::flag(self.valid),
+        }
+    }
+
+    /// Drops all valid units and replaces units with a single nested unit by that unit.
+    fn detailed(mut self) -> OutputUnit {
+        self.errors = mem::take(&mut self.errors)
+            .into_iter()
+            .filter(|unit| !unit.valid)
+            .map(|unit| {
+                let mut unit = unit.detailed();
+                if unit.error.is_none() && unit.errors.len() == 1 {
+                    unit = unit.errors.pop().unwrap();
+                }
+                unit
+            })
+            .collect();
+        self
+    }
+
+    fn collect_errors(self, errors: &mut Vec<OutputUnit>) {
+        for unit in self.errors {
+            if unit.error.is_some() {
+                errors.push(OutputUnit {
+                    errors: vec![],
+                    ..unit.clone()
+                });
+            }
+            unit.collect_errors(errors);
+        }
+    }
+}
+
+impl Serialize for OutputUnit {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = ::serde_json::Map::new();
+        map.insert("valid".to_string(), Value::Bool(self.valid));
+        if let Some(ref location) = self.keyword_location {
+            map.insert("keywordLocation".to_string(), to_value(location).unwrap());
+        }
+        if let Some(ref location) = self.absolute_keyword_location {
+            map.insert(
+                "absoluteKeywordLocation".to_string(),
+                to_value(location).unwrap(),
+            );
+        }
+        if let Some(ref location) = self.instance_location {
+            map.insert("instanceLocation".to_string(), to_value(location).unwrap());
+        }
+        if let Some(ref error) = self.error {
+            map.insert("error".to_string(), to_value(error).unwrap());
+        }
+        if !self.errors.is_empty() {
+            let key = if self.valid { "annotations" } else { "errors" };
+            map.insert(key.to_string(), to_value(&self.errors).unwrap());
+        }
+        Value::Object(map).serialize(serializer)
+    }
+}
+
+/// Collects the output units while a schema is validated.
+///
+/// Every evaluated schema adds a unit to the list of the keyword that is evaluated at
+/// the time, `begin_keyword` and `end_keyword` switch between these lists.
+#[derive(Debug, Default)]
+pub(crate) struct Recorder {
+    units: RefCell<Vec<OutputUnit>>,
+}
+
+impl Recorder {
+    pub fn begin_keyword(&self) -> Vec<OutputUnit> {
+        mem::take(&mut *self.units.borrow_mut())
+    }
+
+    pub fn end_keyword(
+        &self,
+        outer: Vec<OutputUnit>,
+        keyword_location: String,
+        absolute_keyword_location: String,
+        instance_location: &str,
+        state: &super::ValidationState,
+        own: &[usize],
+    ) -> OutputUnit {
+        let nested = mem::replace(&mut *self.units.borrow_mut(), outer);
+        let mut unit = OutputUnit::new(
+            state.is_valid(),
+            keyword_location,
+            absolute_keyword_location,
+            instance_location.to_string(),
+        );
+
+        // errors of custom keywords can not record their location, they are attributed
+        // to the innermost keyword
+        let own_errors: Vec<&dyn ValicoError> = own
+            .iter()
+            .map(|idx| &*state.errors[*idx])
+            .filter(|error| error.get_keyword_location().is_some() || nested.is_empty())
+            .collect();
+
+        if nested.is_empty() && own_errors.len() == 1 {
+            let error = own_errors[0];
+            unit.error = Some(message(error));
+            unit.locate(error);
+        } else {
+            unit.errors = nested;
+            for error in own_errors {
+                let mut error_unit = OutputUnit {
+                    valid: false,
+                    error: Some(message(error)),
+                    errors: vec![],
+                    ..unit.clone()
+                };
+                error_unit.locate(error);
+                unit.errors.push(error_unit);
+            }
+        }
+        unit
+    }
+
+    pub fn add_schema(
+        &self,
+        keyword_location: String,
+        absolute_location: String,
+        instance_location: &str,
+        valid: bool,
+        keywords: Vec<OutputUnit>,
+    ) {
+        let mut unit = OutputUnit::new(
+            valid,
+            keyword_location,
+            absolute_location,
+            instance_location.to_string(),
+        );
+        unit.errors = keywords;
+        self.units.borrow_mut().push(unit);
+    }
+
+    /// Replaces the unit of the schema that the validation started with by the error
+    /// that aborted the validation.
+    pub fn abort(&self, error: &dyn ValicoError) {
+        let mut error_unit = OutputUnit {
+            error: Some(message(error)),
+            ..OutputUnit::flag(false)
+        };
+        error_unit.locate(error);
+
+        let mut units = self.units.borrow_mut();
+        let mut unit = units.pop().unwrap_or_else(|| error_unit.clone());
+        unit.valid = false;
+        unit.error = None;
+        unit.errors = vec![error_unit];
+        units.push(unit);
+    }
+
+    /// Returns the unit of the schema that the validation started with.
+    pub fn finish(self) -> OutputUnit {
+        self.units.into_inner().pop().unwrap()
+    }
+}
+
+fn message(error: &dyn ValicoError) -> String {
+    match error.get_detail() {
+        Some(detail) => format!("{}: {}", error.get_title(), detail),
+        None => error.get_title().to_string(),
+    }
+}
+
+#[cfg(test)]
+fn polygon() -> super::Scope {
+    let mut scope = super::Scope::new().set_version(super::SchemaVersion::Draft2019_09);
+    scope
+        .compile(
+            json!({
+                "$id": "http://example.com/polygon",
+                "$defs": {
+                    "point": {
+                        "type": "object",
+                        "properties": {
+                            "x": { "type": "number" },
+                            "y": { "type": "number" }
+                        },
+                        "additionalProperties": false,
+                        "required": [ "x", "y" ]
+                    }
+                },
+                "type": "array",
+                "items": { "$ref": "#/$defs/point" },
+                "minItems": 3
+            }),
+            false,
+        )
+        .unwrap();
+    scope
+}
+
+#[cfg(test)]
+fn locations(units: &[OutputUnit]) -> Vec<(&str, &str)> {
+    // keywords are not evaluated in a fixed order
+    let mut locations: Vec<_> = units
+        .iter()
+        .map(|unit| {
+            (
+                unit.keyword_location.as_deref().unwrap(),
+                unit.instance_location.as_deref().unwrap(),
+            )
+        })
+        .collect();
+    locations.sort();
+    locations
+}
+
+#[cfg(test)]
+fn unit<'a>(units: &'a [OutputUnit], keyword_location: &str) -> &'a OutputUnit {
+    units
+        .iter()
+        .find(|unit| unit.keyword_location.as_deref() == Some(keyword_location))
+        .unwrap()
+}
+
+#[test]
+fn error_locations() {
+    let scope = polygon();
+    let schema = scope
+        .resolve(&url::Url::parse("http://example.com/polygon").unwrap())
+        .unwrap();
+    let state = schema.validate(&json!([{ "x": 2.5, "y": 1.3 }, { "x": 1, "z": 6.7 }]));
+    let mut locations: Vec<_> = state
+        .errors
+        .iter()
+        .map(|error| {
+            (
+                error.get_keyword_location().unwrap(),
+                error.get_schema_path().unwrap(),
+                error.get_schema_url().unwrap(),
+            )
+        })
+        .collect();
+    locations.sort_unstable();
+    assert_eq!(
+        locations,
+        vec![
+            (
+                "/items/$ref/additionalProperties",
+                "/$defs/point/additionalProperties",
+                "http://example.com/polygon#/$defs/point/additionalProperties"
+            ),
+            (
+                "/items/$ref/required",
+                "/$defs/point/required",
+                "http://example.com/polygon#/$defs/point/required"
+            ),
+            (
+                "/minItems",
+                "/minItems",
+                "http://example.com/polygon#/minItems"
+            )
+        ]
+    );
+}
+
+#[test]
+fn output_formats() {
+    let scope = polygon();
+    let schema = scope
+        .resolve(&url::Url::parse("http://example.com/polygon").unwrap())
+        .unwrap();
+    let data = json!([{ "x": 2.5, "y": 1.3 }, { "x": 1, "z": 6.7 }]);
+
+    assert_eq!(
+        serde_json::to_value(schema.validate_with_output(&data, OutputFormat::Flag)).unwrap(),
+        json!({ "valid": false })
+    );
+
+    let basic = schema.validate_with_output(&data, OutputFormat::Basic);
+    assert!(!basic.valid);
+    assert_eq!(
+        locations(&basic.errors),
+        vec![
+            ("/items/$ref/additionalProperties", "/1"),
+            ("/items/$ref/required", "/1/y"),
+            ("/minItems", "")
+        ]
+    );
+    assert_eq!(
+        unit(&basic.errors, "/minItems")
+            .absolute_keyword_location
+            .as_deref(),
+        Some("http://example.com/polygon#/minItems")
+    );
+
+    let detailed = schema.validate_with_output(&data, OutputFormat::Detailed);
+    assert_eq!(
+        locations(&detailed.errors),
+        vec![("/items/$ref", "/1"), ("/minItems", "")]
+    );
+    assert_eq!(
+        unit(&detailed.errors, "/items/$ref")
+            .absolute_keyword_location
+            .as_deref(),
+        Some("http://example.com/polygon#/$defs/point")
+    );
+    assert_eq!(
+        locations(&unit(&detailed.errors, "/items/$ref").errors),
+        vec![
+            ("/items/$ref/additionalProperties", "/1"),
+            ("/items/$ref/required", "/1/y")
+        ]
+    );
+
+    let verbose = schema.validate_with_output(&data, OutputFormat::Verbose);
+    assert_eq!(
+        locations(&verbose.errors),
+        vec![("/items", ""), ("/minItems", ""), ("/type", "")]
+    );
+    assert!(unit(&verbose.errors, "/type").valid);
+    assert_eq!(
+        locations(&unit(&verbose.errors, "/items").errors),
+        vec![("/items", "/0"), ("/items", "/1")]
+    );
+
+    let valid = json!([{ "x": 0, "y": 0 }, { "x": 1, "y": 0 }, { "x": 0, "y": 1 }]);
+    let output =
+        serde_json::to_value(schema.validate_with_output(&valid, OutputFormat::Verbose)).unwrap();
+    assert_eq!(output["valid"], json!(true));
+    assert_eq!(output["annotations"].as_array().unwrap().len(), 3);
+    assert_eq!(
+        serde_json::to_value(schema.validate_with_output(&valid, OutputFormat::Basic)).unwrap(),
+        json!({ "valid": true })
+    );
+}
@@ -0,0 +1,512 @@
+//! Compiler-style diagnostics for validation errors, located in the source text of the
+//! validated instance.
+
+use serde::{Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+use super::super::common::error::ValicoError;
+use super::best_match;
+use super::errors;
+use super::validators::ValidationState;
+
+/// A position in the source text, with the line and column starting at 1. The column
+/// counts characters, the offset bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+}
+
+/// The part of the source text an error refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// A single error, together with its location in the source text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub code: String,
+    pub title: String,
+    pub detail: Option<String>,
+    /// The instance path of the error.
+    pub path: String,
+    pub keyword_location: Option<String>,
+    /// The span of the offending value, or of the name of a property that is not
+    /// allowed. `None` if the path cannot be found in the source text.
+    pub span: Option<Span>,
+    /// A declared property that a property that is not allowed is most likely a
+    /// misspelling of.
+    pub suggestion: Option<String>,
+}
+
+/// The errors of a validation, located in the JSON text of the validated instance.
+///
+/// `Display` renders the errors like a compiler, with the offending line and a caret
+/// below the offending value, while `Serialize` gives them as JSON with their spans.
+#[derive(Debug)]
+pub struct Report<'a> {
+    source: &'a str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Report<'a> {
+    /// Locates the errors of `state` in `source`, the text `state` was validated from.
+    ///
+    /// A failed `anyOf` or `oneOf` is reported as the best error of its most relevant
+    /// branch, see `ValidationState::best_error`.
+    pub fn new(source: &'a str, state: &ValidationState) -> Report<'a> {
+        let locations = Locator::new(source, false).locate();
+        let lines = LineIndex::new(source);
+
+        let diagnostics = state
+            .errors
+            .iter()
+            .map(|error| {
+                let error = best_match::best_error(std::slice::from_ref(error)).unwrap();
+                diagnose(error, &locations, &lines)
+            })
+            .collect();
+        Report {
+            source,
+            diagnostics,
+        }
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+}
+
+fn diagnose(
+    error: &dyn ValicoError,
+    locations: &HashMap<String, Location>,
+    lines: &LineIndex<'_>,
+) -> Diagnostic {
+    let path = error.get_path();
+    let additional = error.downcast_ref::<errors::Properties>();
+
+    let span = match additional {
+        Some(additional) => locations
+            .get(&[path, additional.property.as_str()].join("/"))
+            .and_then(|location| location.key),
+        None => None,
+    }
+    .or_else(|| {
+        // the closest enclosing value that is found, e.g. for a missing property
+        let mut path = path;
+        loop {
+            if let Some(location) = locations.get(path) {
+                return Some(location.value);
+            }
+            path = &path[..path.rfind('/')?];
+        }
+    });
+
+    Diagnostic {
+        code: error.get_code().to_string(),
+        title: error.get_title().to_string(),
+        detail: error.get_detail().map(str::to_string),
+        path: path.to_string(),
+        keyword_location: error.get_keyword_location().map(str::to_string),
+        span: span.map(|(start, end)| Span {
+            start: lines.position(start),
+            end: lines.position(end),
+        }),
+        suggestion: additional
+            .and_then(|additional| suggest(&additional.property, &additional.known)),
+    }
+}
+
+/// The known name closest to `name`, if it is close enough to be a misspelling.
+fn suggest(name: &str, known: &[String]) -> Option<String> {
+    let limit = std::cmp::max(1, name.chars().count() / 3);
+    known
+        .iter()
+        .map(|candidate| (distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.clone())
+}
+
+/// The edit distance between two strings in characters, where swapping two adjacent
+/// characters counts as a single edit.
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+impl<'a> fmt::Display for Report<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, diagnostic) in self.diagnostics.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "error[{}]: {}", diagnostic.code, diagnostic.title)?;
+            let span = match diagnostic.span {
+                Some(span) => span,
+                None => {
+                    writeln!(f, "  --> {}", display_path(&diagnostic.path))?;
+                    if let Some(ref detail) = diagnostic.detail {
+                        writeln!(f, "  = {detail}")?;
+                    }
+                    continue;
+                }
+            };
+
+            let number = span.start.line.to_string();
+            let gutter = " ".repeat(number.len());
+            let line = self
+                .source
+                .lines()
+                .nth(span.start.line - 1)
+                .unwrap_or_default();
+            // the caret ends with the line for values that span several lines
+            let width = if span.end.line == span.start.line {
+                span.end.column - span.start.column
+            } else {
+                line.chars().count() + 1 - span.start.column
+            };
+
+            writeln!(
+                f,
+                "{}--> {}:{} at {}",
+                gutter,
+                span.start.line,
+                span.start.column,
+                display_path(&diagnostic.path)
+            )?;
+            writeln!(f, "{gutter} |")?;
+            writeln!(f, "{number} | {line}")?;
+            write!(
+                f,
+                "{} | {}{}",
+                gutter,
+                " ".repeat(span.start.column - 1),
+                "^".repeat(std::cmp::max(width, 1))
+            )?;
+            match diagnostic.detail {
+                Some(ref detail) => writeln!(f, " {detail}")?,
+                None => writeln!(f)?,
+            }
+            if let Some(ref suggestion) = diagnostic.suggestion {
+                writeln!(f, "{gutter} = help: did you mean `{suggestion}`?")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn display_path(path: &str) -> &str {
+    if path.is_empty() {
+        "/"
+    } else {
+        path
+    }
+}
+
+impl<'a> Serialize for Report<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let position = |position: Position| {
+            json!({
+                "line": position.line,
+                "column": position.column,
+                "offset": position.offset,
+            })
+        };
+        let diagnostics = self
+            .diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let mut map = Map::new();
+                map.insert("code".to_string(), json!(diagnostic.code));
+                map.insert("title".to_string(), json!(diagnostic.title));
+                if let Some(ref detail) = diagnostic.detail {
+                    map.insert("detail".to_string(), json!(detail));
+                }
+                map.insert("path".to_string(), json!(diagnostic.path));
+                if let Some(ref keyword_location) = diagnostic.keyword_location {
+                    map.insert("keywordLocation".to_string(), json!(keyword_location));
+                }
+                if let Some(span) = diagnostic.span {
+                    map.insert(
+                        "span".to_string(),
+                        json!({ "start": position(span.start), "end": position(span.end) }),
+                    );
+                }
+                if let Some(ref suggestion) = diagnostic.suggestion {
+                    map.insert("suggestion".to_string(), json!(suggestion));
+                }
+                Value::Object(map)
+            })
+            .collect();
+        Value::Array(diagnostics).serialize(serializer)
+    }
+}
+
+/// The byte offsets of the start of every line.
+pub(crate) struct LineIndex<'a> {
+    source: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub(crate) fn new(source: &'a str) -> LineIndex<'a> {
+        let mut starts = vec![0];
+        starts.extend(source.match_indices('\n').map(|(idx, _)| idx + 1));
+        LineIndex { source, starts }
+    }
+
+    pub(crate) fn position(&self, offset: usize) -> Position {
+        let line = match self.starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        };
+        let start = self.starts[line];
+        Position {
+            line: line + 1,
+            column: self.source[start..offset].chars().count() + 1,
+            offset,
+        }
+    }
+}
+
+/// The byte ranges of a value and, for properties, of their name.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Location {
+    pub(crate) value: (usize, usize),
+    pub(crate) key: Option<(usize, usize)>,
+}
+
+/// Finds the locations of all values of a JSON text by their instance path, or by their
+/// JSON pointer if `escape` is set.
+pub(crate) struct Locator<'a> {
+    bytes: &'a [u8],
+    source: &'a str,
+    escape: bool,
+    offset: usize,
+    locations: HashMap<String, Location>,
+}
+
+impl<'a> Locator<'a> {
+    pub(crate) fn new(source: &'a str, escape: bool) -> Locator<'a> {
+        Locator {
+            bytes: source.as_bytes(),
+            source,
+            escape,
+            offset: 0,
+            locations: HashMap::new(),
+        }
+    }
+
+    /// The locations found up to the first syntax error, if any.
+    pub(crate) fn locate(mut self) -> HashMap<String, Location> {
+        self.value(String::new(), None);
+        self.locations
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.bytes.get(self.offset) {
+            self.offset += 1;
+        }
+    }
+
+    fn value(&mut self, path: String, key: Option<(usize, usize)>) -> Option<()> {
+        self.skip_whitespace();
+        let start = self.offset;
+        match *self.bytes.get(self.offset)? {
+            b'{' => {
+                self.offset += 1;
+                self.skip_whitespace();
+                if self.bytes.get(self.offset) == Some(&b'}') {
+                    self.offset += 1;
+                } else {
+                    loop {
+                        self.skip_whitespace();
+                        let key_start = self.offset;
+                        self.string()?;
+                        let mut name: String =
+                            serde_json::from_str(&self.source[key_start..self.offset]).ok()?;
+                        if self.escape {
+                            name = name.replace('~', "~0").replace('/', "~1");
+                        }
+                        let key = Some((key_start, self.offset));
+                        self.skip_whitespace();
+                        self.expect(b':')?;
+                        self.value(format!("{path}/{name}"), key)?;
+                        if !self.separator(b'}')? {
+                            break;
+                        }
+                    }
+                }
+            }
+            b'[' => {
+                self.offset += 1;
+                self.skip_whitespace();
+                if self.bytes.get(self.offset) == Some(&b']') {
+                    self.offset += 1;
+                } else {
+                    let mut idx = 0;
+                    loop {
+                        self.value(format!("{path}/{idx}"), None)?;
+                        idx += 1;
+                        if !self.separator(b']')? {
+                            break;
+                        }
+                    }
+                }
+            }
+            b'"' => self.string()?,
+            _ => {
+                while let Some(byte) = self.bytes.get(self.offset) {
+                    if !byte.is_ascii_alphanumeric() && !matches!(byte, b'-' | b'+' | b'.') {
+                        break;
+                    }
+                    self.offset += 1;
+                }
+                if self.offset == start {
+                    return None;
+                }
+            }
+        }
+        self.locations.insert(
+            path,
+            Location {
+                value: (start, self.offset),
+                key,
+            },
+        );
+        Some(())
+    }
+
+    fn string(&mut self) -> Option<()> {
+        self.expect(b'"')?;
+        loop {
+            match *self.bytes.get(self.offset)? {
+                b'"' => {
+                    self.offset += 1;
+                    return Some(());
+                }
+                b'\\' => self.offset += 2,
+                _ => self.offset += 1,
+            }
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        if self.bytes.get(self.offset) == Some(&byte) {
+            self.offset += 1;
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    /// Consumes a `,` and returns `true`, or the closing bracket and returns `false`.
+    fn separator(&mut self, close: u8) -> Option<bool> {
+        self.skip_whitespace();
+        match *self.bytes.get(self.offset)? {
+            b',' => {
+                self.offset += 1;
+                Some(true)
+            }
+            byte if byte == close => {
+                self.offset += 1;
+                Some(false)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+use super::scope::Scope;
+
+#[test]
+fn report_errors() {
+    let mut scope = Scope::new();
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "properties": {
+                    "name": { "type": "string" },
+                    "port": { "maximum": 65535 },
+                    "tags": { "items": { "minLength": 1 } }
+                },
+                "additionalProperties": false
+            }),
+            true,
+        )
+        .unwrap();
+
+    let source = "{\n  \"name\": \"web\",\n  \"prot\": 8080,\n  \"port\": 70000,\n  \"tags\": [\"a\", \"\"]\n}";
+    let state = schema.validate(&serde_json::from_str(source).unwrap());
+    let report = Report::new(source, &state);
+
+    let spans: Vec<(&str, &str, Option<String>)> = report
+        .diagnostics()
+        .iter()
+        .map(|diagnostic| {
+            let span = diagnostic.span.unwrap();
+            (
+                diagnostic.code.as_str(),
+                &source[span.start.offset..span.end.offset],
+                diagnostic.suggestion.clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        spans,
+        vec![
+            ("maximum", "70000", None),
+            ("properties", "\"prot\"", Some("port".to_string())),
+            ("min_length", "\"\"", None),
+        ]
+    );
+
+    let text = report.to_string();
+    assert!(text.contains("3 |   \"prot\": 8080,\n  |   ^^^^^^"));
+    assert!(text.contains("= help: did you mean `port`?"));
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(
+        json[0]["span"],
+        json!({
+            "start": { "line": 4, "column": 11, "offset": 45 },
+            "end": { "line": 4, "column": 16, "offset": 50 }
+        })
+    );
+}
+
+#[test]
+fn suggest_misspellings() {
+    let known = vec!["address".to_string(), "name".to_string()];
+    assert_eq!(suggest("adress", &known), Some("address".to_string()));
+    assert_eq!(suggest("nmae", &known), Some("name".to_string()));
+    assert_eq!(suggest("colour", &known), None);
+}
@@ -0,0 +1,161 @@
+use serde_json::Value;
+use std::collections;
+use std::fmt;
+use std::fs;
+use std::path;
+use url::Url;
+
+use super::schema;
+
+/// Provides the documents of `$ref` targets that have not been compiled into a `Scope`.
+///
+/// Resolvers are registered with `Scope::add_resolver` and are asked for every referenced
+/// document (the URL is passed without fragment) that is unknown to the scope when a schema
+/// is compiled. The returned document is compiled and cached in the scope.
+pub trait SchemaResolver: Send + Sync {
+    /// Returns the document for `url`, or `None` if this resolver does not know it.
+    fn resolve(&self, url: &Url) -> Result<Option<Value>, schema::SchemaError>;
+}
+
+impl fmt::Debug for dyn SchemaResolver + 'static {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("<resolver>")
+    }
+}
+
+/// Loads documents from the file system, by mapping base URIs to directories.
+///
+/// ```
+/// use valico::json_schema::resolver::DirectoryResolver;
+///
+/// let resolver = DirectoryResolver::new().map("http://localhost:1234/", "tests/schema/remotes");
+/// ```
+#[derive(Debug, Default)]
+pub struct DirectoryResolver {
+    mappings: Vec<(Url, path::PathBuf)>,
+}
+
+impl DirectoryResolver {
+    pub fn new() -> DirectoryResolver {
+        DirectoryResolver::default()
+    }
+
+    /// Serves the URLs below `base_uri` from the files with the remaining path below
+    /// `dir`, e.g. `http://localhost:1234/nested/a.json` from `dir/nested/a.json` for the
+    /// base URI `http://localhost:1234/`. The query of a URL is ignored. A base URI that
+    /// is not an absolute URL matches nothing.
+    pub fn map<P: Into<path::PathBuf>>(mut self, base_uri: &str, dir: P) -> Self {
+        if let Ok(mut base_uri) = Url::parse(base_uri) {
+            if !base_uri.path().ends_with('/') {
+                base_uri.set_path(&format!("{}/", base_uri.path()));
+            }
+            self.mappings.push((base_uri, dir.into()));
+        }
+        self
+    }
+}
+
+/// The path of `url` relative to `base_uri`, if `url` is below it. Segments that could
+/// leave the directory of `base_uri`, such as `..` or an encoded `/`, are rejected.
+fn relative_path(base_uri: &Url, url: &Url) -> Option<path::PathBuf> {
+    if url.scheme() != base_uri.scheme()
+        || url.host() != base_uri.host()
+        || url.port_or_known_default() != base_uri.port_or_known_default()
+    {
+        return None;
+    }
+    let mut segments = url.path_segments()?;
+    // the last segment of the base URI is the empty one after its trailing slash
+    for base_segment in base_uri
+        .path_segments()?
+        .filter(|segment| !segment.is_empty())
+    {
+        if segments.next()? != base_segment {
+            return None;
+        }
+    }
+
+    let mut relative = path::PathBuf::new();
+    for segment in segments {
+        let segment = percent_encoding::percent_decode_str(segment)
+            .decode_utf8()
+            .ok()?;
+        let mut components = path::Path::new(segment.as_ref()).components();
+        match (components.next(), components.next()) {
+            (Some(path::Component::Normal(part)), None) if part == segment.as_ref() => {
+                relative.push(part)
+            }
+            _ => return None,
+        }
+    }
+    if relative.as_os_str().is_empty() {
+        None
+    } else {
+        Some(relative)
+    }
+}
+
+impl SchemaResolver for DirectoryResolver {
+    fn resolve(&self, url: &Url) -> Result<Option<Value>, schema::SchemaError> {
+        for (base_uri, dir) in self.mappings.iter() {
+            let file_path = match relative_path(base_uri, url) {
+                Some(relative) => dir.join(relative),
+                None => continue,
+            };
+            if !file_path.is_file() {
+                continue;
+            }
+            return read_document(&file_path, url).map(Some);
+        }
+        Ok(None)
+    }
+}
+
+/// Reads and parses the document of `url` from a file.
+fn read_document(file_path: &path::Path, url: &Url) -> Result<Value, schema::SchemaError> {
+    let content = read_source(file_path, url)?;
+    serde_json::from_str(&content).map_err(|err| schema::SchemaError::File {
+        path: file_path.to_path_buf(),
+        detail: format!("cannot parse {url}: {err}"),
+    })
+}
+
+/// Reads the source text of the document of `url` from a file.
+pub(crate) fn read_source(
+    file_path: &path::Path,
+    url: &Url,
+) -> Result<String, schema::SchemaError> {
+    fs::read_to_string(file_path).map_err(|err| schema::SchemaError::File {
+        path: file_path.to_path_buf(),
+        detail: format!("cannot read {url}: {err}"),
+    })
+}
+
+/// Serves documents from memory.
+#[derive(Debug, Default)]
+pub struct MemoryResolver {
+    documents: collections::HashMap<String, Value>,
+}
+
+impl MemoryResolver {
+    pub fn new() -> MemoryResolver {
+        MemoryResolver::default()
+    }
+
+    /// Registers the document for `url`, which must not contain a fragment.
+    pub fn insert(&mut self, url: &Url, document: Value) {
+        self.documents.insert(url.to_string(), document);
+    }
+
+    /// Builder-style version of `insert`.
+    pub fn with(mut self, url: &Url, document: Value) -> Self {
+        self.insert(url, document);
+        self
+    }
+}
+
+impl SchemaResolver for MemoryResolver {
+    fn resolve(&self, url: &Url) -> Result<Option<Value>, schema::SchemaError> {
+        Ok(self.documents.get(url.as_str()).cloned())
+    }
+}
@@ -1,11 +1,16 @@
 use serde_json::Value;
 use std::borrow::Cow;
-use std::cell::{Ref, RefCell};
+use std::cell::{Cell, RefCell};
 use std::collections;
 use std::ops;
+use std::path;
+use std::ptr;
 use url::Url;
 
+use super::super::common::error::ValicoError;
+use super::errors;
 use super::keywords;
+use super::output;
 use super::scope;
 use super::validators;
 use super::{helpers, SchemaVersion};
@@ -15,22 +20,84 @@ use std::fmt::{Display, Formatter};
 
 #[derive(Debug)]
 pub struct WalkContext<'a> {
+    /// Base URI of the schema resource that is currently being compiled.
     pub url: &'a Url,
+    /// Path from the document root to the current schema.
     pub fragment: Vec<String>,
+    /// Number of leading `fragment` items that lead up to the resource identified by `url`.
+    pub resource_depth: usize,
     pub scopes: &'a mut collections::HashMap<String, Vec<String>>,
     pub version: SchemaVersion,
+    /// The vocabularies declared by the metaschema of the current resource, if it is known.
+    /// Keywords of standard vocabularies that are not listed here are ignored.
+    pub vocabularies: Option<&'a collections::HashSet<String>>,
 }
 
 impl<'a> WalkContext<'a> {
+    /// The path of the current schema relative to its resource, i.e. the JSON pointer
+    /// that has to be appended to `url` in order to reach it.
     pub fn escaped_fragment(&self) -> String {
         helpers::connect(
-            self.fragment
+            self.fragment[self.resource_depth..]
                 .iter()
                 .map(|s| s.as_ref())
                 .collect::<Vec<&str>>()
                 .as_ref(),
         )
     }
+
+    /// The JSON pointer of the current schema within its document.
+    pub fn pointer(&self) -> String {
+        self.fragment
+            .iter()
+            .map(|key| format!("/{}", key.replace('~', "~0").replace('/', "~1")))
+            .collect()
+    }
+
+    /// The absolute URL of the current schema, relative to its schema resource.
+    pub fn location(&self) -> String {
+        let mut url = self.url.clone();
+        url.set_fragment(None);
+        let fragment = self.escaped_fragment();
+        if fragment.is_empty() {
+            format!("{url}#")
+        } else {
+            format!("{url}#/{fragment}")
+        }
+    }
+
+    /// The location of a keyword of the current schema.
+    pub fn schema_location(&self, keyword: &str) -> SchemaLocation {
+        SchemaLocation {
+            path: format!(
+                "{}/{}",
+                self.pointer(),
+                keyword.replace('~', "~0").replace('/', "~1")
+            ),
+            url: format!("{}/{}", self.location(), helpers::encode(keyword)),
+        }
+    }
+}
+
+/// The location of a schema keyword, as reported with the errors it produces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaLocation {
+    /// The JSON pointer of the keyword within its schema document.
+    pub path: String,
+    /// The absolute URL of the keyword.
+    pub url: String,
+}
+
+impl SchemaLocation {
+    /// The location of the errors of the keyword, before it is known through which
+    /// references the keyword is evaluated.
+    pub fn error_location(&self) -> errors::ErrorLocation {
+        errors::ErrorLocation {
+            keyword_location: None,
+            schema_path: Some(self.path.clone()),
+            schema_url: Some(self.url.clone()),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -40,8 +107,78 @@ pub enum SchemaError {
     IdConflicts,
     NotAnObject,
     UrlParseError(url::ParseError),
+    /// A keyword that is not known, while unknown keywords are banned.
     UnknownKey(String),
-    Malformed { path: String, detail: String },
+    Malformed {
+        /// JSON pointer to the offending value within the schema document.
+        path: String,
+        detail: String,
+    },
+    /// A file of schemas could not be listed, read or parsed, see `Scope::load_dir` and
+    /// `resolver::DirectoryResolver`.
+    File {
+        path: path::PathBuf,
+        detail: String,
+    },
+    /// The schema does not conform to its metaschema, see
+    /// `Scope::validate_against_metaschema`.
+    InvalidSchema {
+        metaschema: Url,
+        violations: Vec<MetaschemaViolation>,
+    },
+    /// An error in a schema compiled from its source text, see `Scope::compile_source`.
+    Located {
+        location: Box<SourceLocation>,
+        error: Box<SchemaError>,
+    },
+}
+
+/// Where an error was found in the source text of a schema document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// The id the document was compiled with, if any.
+    pub url: Option<Url>,
+    /// JSON pointer to the offending value within the document.
+    pub pointer: String,
+    /// The line of the offending value, starting at 1.
+    pub line: usize,
+    /// The column of the offending value in characters, starting at 1.
+    pub column: usize,
+}
+
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        match self.url {
+            Some(ref url) if url.scheme() == "file" => write!(f, "{}:", url.path())?,
+            Some(ref url) => write!(f, "{url}:")?,
+            None => {}
+        }
+        write!(f, "{}:{}", self.line, self.column)?;
+        if !self.pointer.is_empty() {
+            write!(f, " (`{}`)", self.pointer)?;
+        }
+        Ok(())
+    }
+}
+
+/// A single error found while validating a schema against its metaschema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetaschemaViolation {
+    /// JSON pointer to the offending value within the schema.
+    pub pointer: String,
+    pub code: String,
+    pub title: String,
+    pub detail: Option<String>,
+}
+
+impl SchemaError {
+    /// The error without the location it may come with.
+    pub(crate) fn unlocated(self) -> SchemaError {
+        match self {
+            SchemaError::Located { error, .. } => *error,
+            error => error,
+        }
+    }
 }
 
 impl Display for SchemaError {
@@ -51,21 +188,358 @@ impl Display for SchemaError {
             SchemaError::IdConflicts => write!(f, "id conflicts"),
             SchemaError::NotAnObject => write!(f, "not an object"),
             SchemaError::UrlParseError(ref e) => write!(f, "url parse error: {e}"),
-            SchemaError::UnknownKey(ref k) => write!(f, "unknown key: {k}"),
+            SchemaError::UnknownKey(ref key) => write!(f, "unknown key: {key}"),
             SchemaError::Malformed {
                 ref path,
                 ref detail,
             } => write!(f, "malformed path: `{path}`, details: {detail}"),
+            SchemaError::File {
+                ref path,
+                ref detail,
+            } => write!(f, "{}: {detail}", path.display()),
+            SchemaError::InvalidSchema {
+                ref metaschema,
+                ref violations,
+            } => {
+                write!(f, "schema does not conform to {metaschema}")?;
+                for violation in violations.iter() {
+                    write!(f, "; `{}`: {}", violation.pointer, violation.title)?;
+                    if let Some(ref detail) = violation.detail {
+                        write!(f, " ({detail})")?;
+                    }
+                }
+                Ok(())
+            }
+            SchemaError::Located {
+                ref location,
+                ref error,
+            } => write!(f, "{location}: {error}"),
         }
     }
 }
 
-impl Error for SchemaError {}
+impl Error for SchemaError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            SchemaError::Located { ref error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// State that is threaded through all validators while a single instance is validated.
+///
+/// Besides giving access to the `Scope`, the context records the dynamic scope, i.e. the
+/// chain of schema resources that have been entered on the way to the current schema.
+/// This is what `$dynamicRef` is resolved against.
+#[derive(Debug)]
+pub struct ValidationContext<'a> {
+    pub scope: &'a scope::Scope,
+    resource: Option<&'a Url>,
+    /// The schema and the keyword being evaluated, set on the frames that are entered
+    /// for each keyword.
+    keyword: Option<(&'a Schema, &'a KeywordValidator)>,
+    parent: Option<&'a ValidationContext<'a>>,
+    recorder: Option<&'a output::Recorder>,
+    /// The number of errors after which validation stops.
+    error_limit: Option<usize>,
+    /// The instance path of the keyword frames and its depth below the validated value.
+    path: Option<&'a str>,
+    depth: usize,
+    budget: Option<&'a Budget>,
+}
+
+/// The state of a validation run that is shared by all of its contexts.
+#[derive(Debug, Default)]
+struct Budget {
+    steps: Cell<usize>,
+    /// The error that aborted the validation.
+    aborted: RefCell<Option<Box<dyn ValicoError>>>,
+}
+
+impl<'a> ValidationContext<'a> {
+    pub fn new(scope: &'a scope::Scope) -> ValidationContext<'a> {
+        ValidationContext {
+            scope,
+            resource: None,
+            keyword: None,
+            parent: None,
+            recorder: None,
+            error_limit: None,
+            path: None,
+            depth: 0,
+            budget: None,
+        }
+    }
+
+    fn enter<'b>(&'b self, resource: &'b Url) -> ValidationContext<'b> {
+        ValidationContext {
+            scope: self.scope,
+            resource: Some(resource),
+            keyword: None,
+            parent: Some(self),
+            recorder: self.recorder,
+            error_limit: self.error_limit,
+            path: self.path,
+            depth: self.depth,
+            budget: self.budget,
+        }
+    }
+
+    fn enter_keyword<'b>(
+        &'b self,
+        schema: &'b Schema,
+        validator: &'b KeywordValidator,
+        path: &'b str,
+        depth: usize,
+    ) -> ValidationContext<'b> {
+        ValidationContext {
+            scope: self.scope,
+            resource: None,
+            keyword: Some((schema, validator)),
+            parent: Some(self),
+            recorder: self.recorder,
+            error_limit: self.error_limit,
+            path: Some(path),
+            depth,
+            budget: self.budget,
+        }
+    }
+
+    fn with_budget<'b>(&'b self, budget: &'b Budget) -> ValidationContext<'b> {
+        ValidationContext {
+            scope: self.scope,
+            resource: self.resource,
+            keyword: self.keyword,
+            parent: self.parent,
+            recorder: self.recorder,
+            error_limit: self.error_limit,
+            path: self.path,
+            depth: self.depth,
+            budget: Some(budget),
+        }
+    }
+
+    /// Checks that evaluating `schema` at the instance `path` makes progress and stays
+    /// within the limits of the scope, and returns the depth of `path`.
+    ///
+    /// Evaluating a schema again at the same instance location, below its own keywords,
+    /// would recurse forever, as does e.g. `{"$ref": "#"}`.
+    fn enter_schema(&self, schema: &Schema, path: &str) -> Result<usize, Box<dyn ValicoError>> {
+        let mut depth = None;
+        let mut context = Some(self);
+        while let Some(current) = context {
+            if let (Some((outer, _)), Some(outer_path)) = (current.keyword, current.path) {
+                if outer_path != path {
+                    depth.get_or_insert(current.depth + 1);
+                    break;
+                }
+                depth.get_or_insert(current.depth);
+                if ptr::eq(outer, schema) {
+                    return Err(Box::new(errors::ReferenceCycle {
+                        path: path.to_string(),
+                        location: errors::ErrorLocation {
+                            keyword_location: Some(self.keyword_location(schema)),
+                            schema_path: Some(schema.pointer.clone()),
+                            schema_url: Some(schema.location.clone()),
+                        },
+                    }));
+                }
+            }
+            context = current.parent;
+        }
+        let depth = depth.unwrap_or(0);
+
+        if self.scope.max_depth.map(|max| depth > max).unwrap_or(false) {
+            return Err(Box::new(errors::MaxDepth {
+                path: path.to_string(),
+                location: errors::ErrorLocation {
+                    keyword_location: Some(self.keyword_location(schema)),
+                    schema_path: Some(schema.pointer.clone()),
+                    schema_url: Some(schema.location.clone()),
+                },
+            }));
+        }
+        if let (Some(max), Some(budget)) = (self.scope.max_steps, self.budget) {
+            budget.steps.set(budget.steps.get() + 1);
+            if budget.steps.get() > max {
+                return Err(Box::new(errors::MaxSteps {
+                    path: path.to_string(),
+                    location: errors::ErrorLocation {
+                        keyword_location: Some(self.keyword_location(schema)),
+                        schema_path: Some(schema.pointer.clone()),
+                        schema_url: Some(schema.location.clone()),
+                    },
+                }));
+            }
+        }
+        Ok(depth)
+    }
+
+    fn is_aborted(&self) -> bool {
+        self.budget
+            .map(|budget| budget.aborted.borrow().is_some())
+            .unwrap_or(false)
+    }
+
+    /// The keyword location of `schema` when it is evaluated in this context, i.e. the
+    /// JSON pointer from the schema the validation started with to `schema`, following
+    /// the references on the way.
+    pub fn keyword_location(&self, schema: &Schema) -> String {
+        let mut context = Some(self);
+        while let Some(current) = context {
+            if let (Some((parent, validator)), Some(outer)) = (current.keyword, current.parent) {
+                let parent_location = outer.keyword_location(parent);
+                let relative = schema
+                    .pointer
+                    .strip_prefix(parent.pointer.as_str())
+                    .filter(|rest| validator.keys.iter().any(|key| is_below(rest, key)));
+                return match relative {
+                    Some(rest) => parent_location + rest,
+                    None => format!("{}/{}", parent_location, validator.keyword),
+                };
+            }
+            context = current.parent;
+        }
+        String::new()
+    }
+
+    /// Resolves a schema in the underlying `Scope`, keeping the current dynamic scope for
+    /// its validation.
+    pub fn resolve<'b>(&'b self, url: &Url) -> Option<ScopedSchema<'b>> {
+        self.scope.resolve(url).map(|schema| ScopedSchema {
+            context: Some(self),
+            ..schema
+        })
+    }
+
+    /// Whether validation can stop because `state` already holds as many errors as the
+    /// caller asked for, see `ScopedSchema::validate_with_limit`.
+    pub fn should_stop(&self, state: &validators::ValidationState) -> bool {
+        self.error_limit
+            .map(|limit| state.errors.len() >= limit)
+            .unwrap_or(false)
+            || self.is_aborted()
+    }
+
+    /// Whether an error limit is set, so that validators may skip work that can only add errors.
+    pub fn has_error_limit(&self) -> bool {
+        self.error_limit.is_some()
+    }
+
+    /// Resolves the target of a validator through its link. References without a link
+    /// have no target in the scope, which reports them as missing.
+    pub fn resolve_ref<'b>(&'b self, reference: &SchemaRef) -> Option<ScopedSchema<'b>> {
+        match reference.link {
+            Some(ref link) => self.scope.get_linked(link).map(|schema| ScopedSchema {
+                scope: self.scope,
+                schema,
+                context: Some(self),
+            }),
+            None => {
+                // a target compiled later is linked then, so this only finds schemas of
+                // scopes that skipped `Scope::link`
+                let target = self.resolve(&reference.url);
+                debug_assert!(
+                    target.is_none(),
+                    "the reference to {} is not linked",
+                    reference.url
+                );
+                target
+            }
+        }
+    }
+
+    /// The schema resources of the dynamic scope, outermost first.
+    pub fn dynamic_scope(&self) -> Vec<&Url> {
+        let mut resources = vec![];
+        let mut context = Some(self);
+        while let Some(current) = context {
+            if let Some(resource) = current.resource {
+                resources.push(resource);
+            }
+            context = current.parent;
+        }
+        resources.reverse();
+        resources
+    }
+
+    /// Resolves `$recursiveRef` to the outermost schema resource of the dynamic scope
+    /// that is reached from the current one through resources with `$recursiveAnchor`.
+    pub fn resolve_recursive_anchor<'b>(&'b self) -> Option<ScopedSchema<'b>> {
+        let mut target = None;
+        for resource in self.dynamic_scope().into_iter().rev() {
+            match self.resolve(resource) {
+                Some(schema) if schema.has_recursive_anchor() => target = Some(schema),
+                _ => break,
+            }
+        }
+        target
+    }
+
+    /// Resolves `$dynamicRef` to the outermost schema in the dynamic scope that declares
+    /// the given `$dynamicAnchor`.
+    pub fn resolve_dynamic_anchor<'b>(&'b self, anchor: &str) -> Option<ScopedSchema<'b>> {
+        self.dynamic_scope().into_iter().find_map(|resource| {
+            let mut url = resource.clone();
+            url.set_fragment(Some(anchor));
+            self.resolve(&url)
+                .filter(|schema| schema.has_dynamic_anchor(anchor))
+        })
+    }
+}
+
+/// The URL of a schema that a validator delegates to.
+///
+/// Once all schemas are compiled, `Scope` links the URL to the location of the target
+/// schema, so that it can be found without resolving the URL during validation.
+#[derive(Debug, Clone)]
+pub struct SchemaRef {
+    pub url: Url,
+    pub(crate) link: Option<Link>,
+}
+
+impl SchemaRef {
+    pub fn new(url: Url) -> SchemaRef {
+        SchemaRef { url, link: None }
+    }
+
+    pub(crate) fn is_linked(&self) -> bool {
+        self.link.is_some()
+    }
+
+    pub(crate) fn set_link(&mut self, link: Link) {
+        self.link = Some(link);
+    }
+
+    pub(crate) fn link(&self) -> Option<&Link> {
+        self.link.as_ref()
+    }
+
+    pub(crate) fn unlink(&mut self) {
+        self.link = None;
+    }
+}
+
+impl From<Url> for SchemaRef {
+    fn from(url: Url) -> SchemaRef {
+        SchemaRef::new(url)
+    }
+}
+
+/// The location of a compiled schema in its `Scope`: the key of its document and the
+/// keys of the subschemas that lead to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Link {
+    pub document: String,
+    pub path: Vec<String>,
+}
 
 #[derive(Debug)]
 pub struct ScopedSchema<'a> {
     scope: &'a scope::Scope,
     schema: &'a Schema,
+    context: Option<&'a ValidationContext<'a>>,
 }
 
 impl<'a> ops::Deref for ScopedSchema<'a> {
@@ -78,15 +552,63 @@ impl<'a> ops::Deref for ScopedSchema<'a> {
 
 impl<'a> ScopedSchema<'a> {
     pub fn new(scope: &'a scope::Scope, schema: &'a Schema) -> ScopedSchema<'a> {
-        ScopedSchema { scope, schema }
+        ScopedSchema {
+            scope,
+            schema,
+            context: None,
+        }
     }
 
     pub fn validate(&self, data: &Value) -> validators::ValidationState {
-        self.schema.validate_in_scope(data, "", self.scope)
+        self.validate_in(data, "")
     }
 
     pub fn validate_in(&self, data: &Value, path: &str) -> validators::ValidationState {
-        self.schema.validate_in_scope(data, path, self.scope)
+        match self.context {
+            Some(context) => self.schema.validate_in_scope(data, path, context),
+            None => self
+                .schema
+                .validate_in_scope(data, path, &ValidationContext::new(self.scope)),
+        }
+    }
+
+    /// Validates the data, but stops as soon as `max_errors` errors are found.
+    ///
+    /// The validators skip the remaining keywords, items, properties and subschemas then,
+    /// so this is much cheaper for invalid data when not all errors are needed.
+    pub fn validate_with_limit(
+        &self,
+        data: &Value,
+        max_errors: usize,
+    ) -> validators::ValidationState {
+        let mut ctx = ValidationContext::new(self.scope);
+        ctx.error_limit = Some(max_errors.max(1));
+        let mut state = self.schema.validate_in_scope(data, "", &ctx);
+        state.errors.truncate(max_errors.max(1));
+        state
+    }
+
+    /// Tells whether the data is valid, stopping at the first error.
+    pub fn is_valid(&self, data: &Value) -> bool {
+        self.validate_with_limit(data, 1).is_valid()
+    }
+
+    /// Validates the data and reports the result in one of the standard output formats.
+    pub fn validate_with_output(
+        &self,
+        data: &Value,
+        format: output::OutputFormat,
+    ) -> output::OutputUnit {
+        let recorder = output::Recorder::default();
+        let mut ctx = ValidationContext::new(self.scope);
+        if format != output::OutputFormat::Flag {
+            ctx.recorder = Some(&recorder);
+        }
+        let state = self.schema.validate_in_scope(data, "", &ctx);
+        if format == output::OutputFormat::Flag {
+            return output::OutputUnit::flag(state.is_valid());
+        }
+        recorder.finish().into_format(format)
     }
 }
 
@@ -95,11 +617,37 @@ impl<'a> ScopedSchema<'a> {
 pub struct Schema {
     pub id: Option<Url>,
     schema: Option<Url>,
-    original: Value,
-    tree: collections::BTreeMap<String, Schema>,
-    validators: validators::Validators,
+    pub(crate) original: Value,
+    pub(crate) tree: collections::BTreeMap<String, Schema>,
+    validators: Vec<KeywordValidator>,
     scopes: collections::HashMap<String, Vec<String>>,
-    default: RefCell<Option<Value>>,
+    default: Option<Value>,
+    pub(crate) version: SchemaVersion,
+    /// The URL of the schema resource this schema belongs to.
+    pub(crate) resource: Url,
+    /// The absolute targets of the references of this schema.
+    pub(crate) references: Vec<Url>,
+    /// The JSON pointer of this schema within its document.
+    pub(crate) pointer: String,
+    /// The absolute URL of this schema.
+    pub(crate) location: String,
+    /// Whether this is a schema, rather than e.g. the object of `properties`.
+    pub(crate) is_schema: bool,
+    /// The annotation keywords of this schema that are collected during validation.
+    annotation_keywords: Vec<&'static str>,
+}
+
+/// A compiled validator together with the keywords it was compiled from.
+#[derive(Debug)]
+struct KeywordValidator {
+    /// The keyword that identifies the validator in keyword locations.
+    keyword: &'static str,
+    /// All keywords handled by the validator.
+    keys: Vec<&'static str>,
+    /// The location of `keyword`, errors of the validator that do not name another
+    /// keyword get this one.
+    location: SchemaLocation,
+    validator: validators::BoxedValidator,
 }
 
 include!(concat!(env!("OUT_DIR"), "/codegen.rs"));
@@ -107,7 +655,13 @@ include!(concat!(env!("OUT_DIR"), "/codegen.rs"));
 pub struct CompilationSettings<'a> {
     pub keywords: &'a keywords::KeywordMap,
     pub ban_unknown_keywords: bool,
+    /// The version used for documents without `$schema`.
     pub schema_version: SchemaVersion,
+    /// The version used for documents whose `$schema` is not a known metaschema,
+    /// `schema_version` is used if this is not set.
+    pub fallback_version: Option<SchemaVersion>,
+    /// Custom metaschemas referenced by `$schema` are looked up here.
+    pub metaschemas: Option<&'a scope::Scope>,
 }
 
 impl<'a> CompilationSettings<'a> {
@@ -120,8 +674,65 @@ impl<'a> CompilationSettings<'a> {
             keywords,
             ban_unknown_keywords,
             schema_version,
+            fallback_version: None,
+            metaschemas: None,
         }
     }
+
+    /// Determines the version of a schema resource from its `$schema`, falling back to
+    /// `default` if it has none.
+    pub(crate) fn detect_version(
+        &self,
+        def: &Value,
+        default: SchemaVersion,
+    ) -> Result<SchemaVersion, SchemaError> {
+        let url = match helpers::parse_url_key("$schema", def)? {
+            Some(url) => url,
+            None => return Ok(default),
+        };
+        Ok(SchemaVersion::from_metaschema_url(&url)
+            .or_else(|| {
+                self.metaschemas
+                    .and_then(|scope| scope.resolve(&url))
+                    .map(|metaschema| metaschema.version)
+            })
+            .or(self.fallback_version)
+            .unwrap_or(self.schema_version))
+    }
+
+    /// Looks up the metaschema of a schema resource and returns the vocabularies it
+    /// declares. Returns `None` if the metaschema is unknown or does not use `$vocabulary`.
+    fn detect_vocabularies(
+        &self,
+        def: &Value,
+        version: SchemaVersion,
+        pointer: &str,
+    ) -> Result<Option<collections::HashSet<String>>, SchemaError> {
+        if version < SchemaVersion::Draft2019_09 {
+            return Ok(None);
+        }
+        let metaschema = match (helpers::parse_url_key("$schema", def)?, self.metaschemas) {
+            (Some(url), Some(scope)) => scope.resolve(&url),
+            _ => None,
+        };
+        let declared = match metaschema.as_ref().and_then(|m| m.declared_vocabularies()) {
+            Some(declared) => declared,
+            None => return Ok(None),
+        };
+
+        let mut enabled = collections::HashSet::new();
+        for (uri, required) in declared {
+            if keywords::KNOWN_VOCABULARIES.contains(&uri) {
+                enabled.insert(uri.to_string());
+            } else if required {
+                return Err(SchemaError::Malformed {
+                    path: format!("{pointer}/$schema"),
+                    detail: format!("The required vocabulary {uri} is not supported"),
+                });
+            }
+        }
+        Ok(Some(enabled))
+    }
 }
 
 impl Schema {
@@ -136,22 +747,21 @@ impl Schema {
             return Err(SchemaError::NotAnObject);
         }
 
-        let mut id = if let Some(id) = external_id {
+        let version = settings.detect_version(&def, settings.schema_version)?;
+        let vocabularies = settings.detect_vocabularies(&def, version, "")?;
+
+        let id = if let Some(id) = external_id {
             id
         } else {
-            helpers::parse_url_key("$id", &def)?.unwrap_or_else(helpers::generate_id)
+            // a relative id is resolved against a generated base
+            helpers::parse_url_key_with_base(
+                helpers::id_key(version),
+                &def,
+                &helpers::generate_id(),
+            )?
+            .unwrap_or_else(helpers::generate_id)
         };
 
-        if settings.schema_version >= SchemaVersion::Draft2019_09 {
-            if let Some(anchor) = def.get("$anchor") {
-                let anchor = anchor.as_str().ok_or_else(|| SchemaError::Malformed {
-                    path: "".to_string(),
-                    detail: "$anchor must be a string".to_string(),
-                })?;
-                id.set_fragment(Some(anchor));
-            };
-        }
-
         let schema = helpers::parse_url_key("$schema", &def)?;
 
         let (tree, mut scopes) = {
@@ -160,6 +770,12 @@ impl Schema {
 
             let mut scopes = collections::HashMap::new();
 
+            for anchor in parse_anchors(&def, version)? {
+                let mut anchor_url = id.clone();
+                anchor_url.set_fragment(Some(anchor));
+                scopes.insert(anchor_url.into(), vec![]);
+            }
+
             for (key, value) in obj.iter() {
                 if !value.is_object() && !value.is_array() && !value.is_boolean() {
                     continue;
@@ -171,8 +787,10 @@ impl Schema {
                 let mut context = WalkContext {
                     url: &id,
                     fragment: vec![key.clone()],
+                    resource_depth: 0,
                     scopes: &mut scopes,
-                    version: settings.schema_version,
+                    version,
+                    vocabularies: vocabularies.as_ref(),
                 };
 
                 let scheme = Schema::compile_sub(
@@ -188,123 +806,179 @@ impl Schema {
             (tree, scopes)
         };
 
-        let validators = Schema::compile_keywords(
-            &def,
-            &WalkContext {
-                url: &id,
-                fragment: vec![],
-                scopes: &mut scopes,
-                version: settings.schema_version,
-            },
-            &settings,
-        )?;
+        let context = WalkContext {
+            url: &id,
+            fragment: vec![],
+            resource_depth: 0,
+            scopes: &mut scopes,
+            version,
+            vocabularies: vocabularies.as_ref(),
+        };
+        let validators = Schema::compile_keywords(&def, &context, &settings)?;
+        let annotation_keywords = annotation_keywords(&def, &context);
+        let location = context.location();
+
+        let references = parse_references(&def, version, &id);
 
         let schema = Schema {
+            resource: id.clone(),
+            references,
+            pointer: String::new(),
+            location,
+            is_schema: true,
             id: Some(id),
             schema,
             original: def,
             tree,
             validators,
             scopes,
-            default: RefCell::new(None),
+            default: None,
+            version,
+            annotation_keywords,
         };
 
         Ok(schema)
     }
 
-    /// The issue here is that it is impossible to explain
-    /// to the Rust borrow checker that I’m traversing a tree, mutating it at the same
-    /// time, and need to occasionally jump back to the root — which is safe in this
-    /// particular case because the tree structure is not touched!
-    ///
-    /// This operation is safe (i.e. it will not panic) as long as it is not called
-    /// while the default value is borrowed, hence unsafe_get_default must not be used
-    /// directly, only via `get_default()` and `has_default()`.
-    fn unsafe_set_default(&self, default: Option<Value>) {
-        self.default.replace(default);
+    /// Whether one of the validators of this schema was compiled from `keyword`.
+    pub(crate) fn has_keyword(&self, keyword: &str) -> bool {
+        self.validators
+            .iter()
+            .any(|validator| validator.keys.contains(&keyword))
     }
 
-    /// Getting references to internally mutable memory is finicky business, we must
-    /// not allow these references to escape. Therefore only internal code is allowed
-    /// to use this function to
-    ///
-    ///  - take a peek at the value, to see whether the option is set, or
-    ///  - take a peek at the value just long enough to clone it.
-    fn unsafe_get_default(&self) -> Ref<Option<Value>> {
-        self.default.borrow()
+    /// The values this schema allows for the property `name` of an object through a
+    /// `const` or `enum` below `properties`, if it restricts them.
+    pub(crate) fn pinned_values(&self, name: &str) -> Option<Vec<&Value>> {
+        // before draft 2019-09 all keywords next to "$ref" are ignored
+        if self.version < SchemaVersion::Draft2019_09 && self.original.get("$ref").is_some() {
+            return None;
+        }
+        if !self.has_keyword("properties") {
+            return None;
+        }
+        let property = self
+            .tree
+            .get("properties")?
+            .tree
+            .get(&helpers::encode(name))?;
+        if property.has_keyword("const") {
+            property.original.get("const").map(|value| vec![value])
+        } else if property.has_keyword("enum") {
+            property
+                .original
+                .get("enum")
+                .and_then(Value::as_array)
+                .map(|values| values.iter().collect())
+        } else {
+            None
+        }
     }
 
     pub fn get_default(&self) -> Option<Value> {
-        self.unsafe_get_default().clone()
+        self.default.clone()
     }
 
     pub fn has_default(&self) -> bool {
-        self.unsafe_get_default().is_some()
+        self.default.is_some()
     }
 
+    /// Computes the defaults of this schema and all of its subschemas.
+    ///
+    /// The defaults are first computed for the whole tree (following references into
+    /// other parts of it) and only then stored, so that the compiled schema does not need
+    /// any interior mutability.
     pub fn add_defaults(&mut self, id: &Url, scope: &scope::Scope) {
-        self.add_defaults_recursive(self, id, scope);
+        let mut defaults = collections::HashMap::new();
+        self.compute_defaults(self, id, scope, &mut defaults);
+        self.store_defaults(&mut defaults);
     }
 
-    fn add_defaults_recursive(&self, top: &Schema, id: &Url, scope: &scope::Scope) {
-        // step 0: bail out if we already have a default (i.e. proof that traversal got here before)
-        if self.has_default() {
-            return;
+    /// Computes the default of this schema (and its subschemas) into `defaults`, keyed by
+    /// the JSON pointer of the schema.
+    fn compute_defaults(
+        &self,
+        top: &Schema,
+        id: &Url,
+        scope: &scope::Scope,
+        defaults: &mut collections::HashMap<String, Option<Value>>,
+    ) -> Option<Value> {
+        // step 0: bail out if the traversal got here before (this also stops cycles)
+        if let Some(default) = defaults.get(&self.pointer) {
+            return default.clone();
         }
+        defaults.insert(self.pointer.clone(), None);
+
+        let default = self.compute_default(top, id, scope, defaults);
+        defaults.insert(self.pointer.clone(), default.clone());
+        default
+    }
 
+    fn compute_default(
+        &self,
+        top: &Schema,
+        id: &Url,
+        scope: &scope::Scope,
+        defaults: &mut collections::HashMap<String, Option<Value>>,
+    ) -> Option<Value> {
         // step 1: walk the tree to apply this recursively
         for (_, schema) in self.tree.iter() {
-            schema.add_defaults_recursive(top, id, scope);
+            schema.compute_defaults(top, id, scope, defaults);
         }
+        let default_of = |schema: &Schema, defaults: &collections::HashMap<_, Option<Value>>| {
+            defaults.get(&schema.pointer).cloned().flatten()
+        };
 
         // step 2: use explicit default if present
         if let Some(default) = self.original.get("default") {
-            self.unsafe_set_default(Some(default.clone()));
-            return;
+            return Some(default.clone());
         }
 
         // step 3: propagate defaults according to the rules
         // 3a: $ref
         if let Some(ref_) = self.original.get("$ref").and_then(|r| r.as_str()) {
             if let Ok(url) = Url::options().base_url(Some(id)).parse(ref_) {
-                // first try to resolve this Url internally so that we can then modify the schema
-                // in case this one has not yet been traversed
+                // first try to resolve this Url internally so that the referenced schema
+                // is traversed in case it has not been yet
                 if let Some(schema) = top.resolve_internal(&url) {
-                    schema.add_defaults_recursive(top, id, scope);
-                    self.unsafe_set_default(schema.get_default());
+                    return schema.compute_defaults(top, id, scope, defaults);
                 } else if let Some(schema) = scope.resolve(&url) {
-                    self.unsafe_set_default(schema.get_default());
+                    return schema.get_default();
                 }
             }
             // $ref is exclusive, i.e. does not tolerate other keywords to be present
-            return;
+            return None;
         }
         // 3b: properties
         if let Some(properties) = self.tree.get("properties") {
             let mut default = serde_json::Map::default();
             for (key, schema) in properties.tree.iter() {
-                if let Some(value) = schema.get_default() {
+                if let Some(value) = default_of(schema, defaults) {
                     default.insert(key.clone(), value);
                 }
             }
             if !default.is_empty() {
-                self.unsafe_set_default(Some(default.into()));
-                return;
+                return Some(default.into());
             }
         }
-        // 3c: items, if array
+        // 3c: items, if array (or prefixItems since draft 2020-12)
         // only create a default, if there are defaults given for all items
+        let items_key = if self.version >= SchemaVersion::Draft2020_12 {
+            "prefixItems"
+        } else {
+            "items"
+        };
         if self
             .original
-            .get("items")
+            .get(items_key)
             .map(|i| i.is_array())
             .unwrap_or(false)
         {
-            let items = self.tree.get("items").unwrap();
+            let items = self.tree.get(items_key).unwrap();
             let mut default = vec![];
             for idx in 0.. {
                 if let Some(schema) = items.tree.get(&idx.to_string()) {
-                    if let Some(def) = schema.get_default() {
+                    if let Some(def) = default_of(schema, defaults) {
                         default.push(def);
                     } else {
                         break;
@@ -314,16 +988,47 @@ impl Schema {
                 }
             }
             if default.len() == items.tree.len() {
-                self.unsafe_set_default(Some(default.into()));
+                return Some(default.into());
             }
         }
+        None
+    }
+
+    fn store_defaults(&mut self, defaults: &mut collections::HashMap<String, Option<Value>>) {
+        if let Some(default) = defaults.remove(&self.pointer) {
+            self.default = default;
+        }
+        for (_, schema) in self.tree.iter_mut() {
+            schema.store_defaults(defaults);
+        }
     }
 
     fn compile_keywords(
         def: &Value,
         context: &WalkContext,
         settings: &CompilationSettings,
-    ) -> Result<validators::Validators, SchemaError> {
+    ) -> Result<Vec<KeywordValidator>, SchemaError> {
+        // keywords of vocabularies that the metaschema does not declare are ignored
+        let filtered;
+        let def = match context.vocabularies {
+            Some(enabled) => {
+                filtered = Value::Object(
+                    def.as_object()
+                        .unwrap()
+                        .iter()
+                        .filter(|(key, _)| {
+                            let vocabularies = keywords::vocabularies(key, context.version);
+                            vocabularies.is_empty()
+                                || vocabularies.iter().any(|v| enabled.contains(*v))
+                        })
+                        .map(|(key, value)| (key.clone(), value.clone()))
+                        .collect(),
+                );
+                &filtered
+            }
+            None => def,
+        };
+
         let mut validators = vec![];
         let mut end_validators = vec![];
         let mut keys: collections::HashSet<&str> = def
@@ -341,10 +1046,25 @@ impl Schema {
                     Some(keyword) => {
                         keyword.consume(&mut keys);
 
-                        let is_exclusive_keyword =
-                            keyword.keyword.is_exclusive(settings.schema_version);
-
-                        if let Some(validator) = keyword.keyword.compile(def, context)? {
+                        let is_exclusive_keyword = keyword.keyword.is_exclusive(context.version);
+
+                        let primary = keyword
+                            .keys
+                            .iter()
+                            .find(|key| def.get(**key).is_some())
+                            .cloned()
+                            .unwrap_or(keyword.keys[0]);
+                        let compiled = keyword
+                            .keyword
+                            .compile(def, context)
+                            .map_err(|error| locate_keyword(error, context, primary))?;
+                        if let Some(validator) = compiled {
+                            let validator = KeywordValidator {
+                                keyword: primary,
+                                keys: keyword.keys.clone(),
+                                location: context.schema_location(primary),
+                                validator,
+                            };
                             if is_exclusive_keyword {
                                 validators = vec![validator];
                                 end_validators = vec![];
@@ -375,8 +1095,15 @@ impl Schema {
 
         if settings.ban_unknown_keywords && !not_consumed.is_empty() {
             for key in not_consumed.iter() {
-                if !ALLOW_NON_CONSUMED_KEYS.contains(&key[..]) {
-                    return Err(SchemaError::UnknownKey((*key).to_string()));
+                // draft 4 names the identifier `id`
+                let is_allowed = ALLOW_NON_CONSUMED_KEYS.contains(&key[..])
+                    || (*key == "id" && context.version == SchemaVersion::Draft4);
+                if !is_allowed {
+                    return Err(locate_keyword(
+                        SchemaError::UnknownKey((*key).to_string()),
+                        context,
+                        key,
+                    ));
                 }
             }
         }
@@ -392,29 +1119,59 @@ impl Schema {
         is_schema: bool,
     ) -> Result<Schema, SchemaError> {
         let def = helpers::convert_boolean_schema(def);
+        let mut version = context.version;
+        let mut vocabularies = context.vocabularies;
+        let own_vocabularies;
 
-        let id = if is_schema {
-            let mut id_url = helpers::parse_url_key_with_base("$id", &def, context.url)?;
-            if keywords.schema_version >= SchemaVersion::Draft2019_09 {
-                if let Some(anchor) = def.get("$anchor") {
-                    let anchor = anchor.as_str().ok_or_else(|| SchemaError::Malformed {
-                        path: "".to_string(),
-                        detail: "$anchor must be a string".to_string(),
-                    })?;
-
-                    // If the "$id" URL is not explicitly overridden, implicitly inherit the parent's URL.
-                    if id_url.is_none() {
-                        id_url = Some(context.url.clone());
-                    }
+        let mut id_url = if is_schema {
+            parse_id(&def, version, context.url)?
+        } else {
+            None
+        };
 
-                    id_url.as_mut().unwrap().set_fragment(Some(anchor));
-                }
+        // "$schema" may switch the version, but only at the root of an embedded resource
+        if is_schema && def.get("$schema").is_some() {
+            let resource_version = keywords.detect_version(&def, version)?;
+            let resource_id = parse_id(&def, resource_version, context.url)?;
+            if resource_id.as_ref().map(is_resource_url).unwrap_or(false) {
+                version = resource_version;
+                id_url = resource_id;
+                own_vocabularies =
+                    keywords.detect_vocabularies(&def, version, &context.pointer())?;
+                vocabularies = own_vocabularies.as_ref();
             }
-            id_url
+        }
+
+        let anchors = if is_schema {
+            parse_anchors(&def, version)?
         } else {
-            None
+            vec![]
         };
 
+        // an id without a fragment starts a new schema resource, which becomes the base
+        // for everything below it
+        let resource = id_url.as_ref().filter(|url| is_resource_url(url)).cloned();
+        let (base, resource_depth) = match resource {
+            Some(ref url) => (url, context.fragment.len()),
+            None => (context.url, context.resource_depth),
+        };
+
+        for anchor in anchors.iter() {
+            let mut anchor_url = base.clone();
+            anchor_url.set_fragment(Some(anchor));
+            context
+                .scopes
+                .insert(anchor_url.into(), context.fragment.clone());
+        }
+
+        let id = id_url.or_else(|| {
+            anchors.first().map(|anchor| {
+                let mut anchor_url = base.clone();
+                anchor_url.set_fragment(Some(anchor));
+                anchor_url
+            })
+        });
+
         let schema = if is_schema {
             helpers::parse_url_key("$schema", &def)?
         } else {
@@ -443,10 +1200,12 @@ impl Schema {
                         || !NON_SCHEMA_KEYS.contains(&key[..]);
 
                     let mut context = WalkContext {
-                        url: id.as_ref().unwrap_or(context.url),
+                        url: base,
                         fragment: current_fragment,
+                        resource_depth,
                         scopes: context.scopes,
-                        version: keywords.schema_version,
+                        version,
+                        vocabularies,
                     };
 
                     let scheme =
@@ -473,10 +1232,12 @@ impl Schema {
                     current_fragment.push(idx.to_string().clone());
 
                     let mut context = WalkContext {
-                        url: id.as_ref().unwrap_or(context.url),
+                        url: base,
                         fragment: current_fragment,
+                        resource_depth,
                         scopes: context.scopes,
-                        version: keywords.schema_version,
+                        version,
+                        vocabularies,
                     };
 
                     let scheme = Schema::compile_sub(value.clone(), &mut context, keywords, true)?;
@@ -494,8 +1255,27 @@ impl Schema {
                 .insert(id.clone().unwrap().into(), context.fragment.clone());
         }
 
+        let context = WalkContext {
+            url: base,
+            fragment: context.fragment.clone(),
+            resource_depth,
+            scopes: context.scopes,
+            version,
+            vocabularies,
+        };
         let validators = if is_schema && def.is_object() {
-            Schema::compile_keywords(&def, context, keywords)?
+            Schema::compile_keywords(&def, &context, keywords)?
+        } else {
+            vec![]
+        };
+
+        let references = if is_schema && def.is_object() {
+            parse_references(&def, version, base)
+        } else {
+            vec![]
+        };
+        let annotation_keywords = if is_schema && def.is_object() {
+            annotation_keywords(&def, &context)
         } else {
             vec![]
         };
@@ -507,12 +1287,134 @@ impl Schema {
             tree,
             validators,
             scopes: collections::HashMap::new(),
-            default: RefCell::new(None),
+            default: None,
+            version,
+            resource: base.clone(),
+            references,
+            pointer: context.pointer(),
+            location: context.location(),
+            is_schema,
+            annotation_keywords,
         };
 
         Ok(schema)
     }
 
+    /// Lists the references of this schema and all of its subschemas as pairs of the
+    /// JSON pointer of the referencing schema (relative to this one) and the absolute
+    /// reference target.
+    pub fn references(&self) -> Vec<(String, &Url)> {
+        let mut references = vec![];
+        self.collect_urls(
+            "",
+            &|schema| schema.references.iter().collect(),
+            &mut references,
+        );
+        references
+    }
+
+    /// Lists the URLs of all schemas that the validators of this schema and of its
+    /// subschemas delegate to, paired like in `references`.
+    pub fn validator_references(&self) -> Vec<(String, &Url)> {
+        let mut references = vec![];
+        self.collect_urls(
+            "",
+            &|schema| {
+                schema
+                    .validators
+                    .iter()
+                    .flat_map(|validator| validator.validator.references())
+                    .map(|reference| &reference.url)
+                    .collect()
+            },
+            &mut references,
+        );
+        references
+    }
+
+    fn collect_urls<'s, F>(&'s self, path: &str, urls_of: &F, urls: &mut Vec<(String, &'s Url)>)
+    where
+        F: Fn(&'s Schema) -> Vec<&'s Url>,
+    {
+        for url in urls_of(self) {
+            urls.push((path.to_string(), url));
+        }
+        for (key, schema) in self.tree.iter() {
+            schema.collect_urls(&format!("{}/{}", path, key), urls_of, urls);
+        }
+    }
+
+    /// Visits the references of the validators of this schema and of its subschemas.
+    pub(crate) fn visit_references<F>(&self, visit: &mut F)
+    where
+        F: FnMut(&SchemaRef),
+    {
+        for validator in self.validators.iter() {
+            for reference in validator.validator.references() {
+                visit(reference);
+            }
+        }
+        for schema in self.tree.values() {
+            schema.visit_references(visit);
+        }
+    }
+
+    pub(crate) fn visit_references_mut<F>(&mut self, visit: &mut F)
+    where
+        F: FnMut(&mut SchemaRef),
+    {
+        for validator in self.validators.iter_mut() {
+            for reference in validator.validator.references_mut() {
+                visit(reference);
+            }
+        }
+        for schema in self.tree.values_mut() {
+            schema.visit_references_mut(visit);
+        }
+    }
+
+    pub(crate) fn visit_validators<F>(&self, visit: &mut F)
+    where
+        F: FnMut(&validators::BoxedValidator),
+    {
+        for validator in self.validators.iter() {
+            visit(&validator.validator);
+        }
+        for schema in self.tree.values() {
+            schema.visit_validators(visit);
+        }
+    }
+
+    pub(crate) fn visit_validators_mut<F>(&mut self, visit: &mut F)
+    where
+        F: FnMut(&mut validators::BoxedValidator),
+    {
+        for validator in self.validators.iter_mut() {
+            visit(&mut validator.validator);
+        }
+        for schema in self.tree.values_mut() {
+            schema.visit_validators_mut(visit);
+        }
+    }
+
+    /// The path of this schema in the tree of its document, see `Link`.
+    pub(crate) fn tree_path(&self) -> Vec<String> {
+        self.pointer
+            .split('/')
+            .skip(1)
+            .map(|key| helpers::encode(&key.replace("~1", "/").replace("~0", "~")))
+            .collect()
+    }
+
+    /// Follows the path of a `Link` from this (document) schema.
+    pub(crate) fn get_linked(&self, path: &[String]) -> Option<&Schema> {
+        let mut schema = self;
+        for key in path {
+            schema = schema.tree.get(key)?;
+        }
+        Some(schema)
+    }
+
     pub fn resolve(&self, id: &str) -> Option<&Schema> {
         let path = self.scopes.get(id);
         path.map(|path| {
@@ -564,21 +1466,175 @@ impl Schema {
 }
 
 impl Schema {
+    /// The vocabularies declared by this schema through `$vocabulary`, together with
+    /// the flag whether they are required. Returns `None` if it declares none.
+    pub fn declared_vocabularies(&self) -> Option<Vec<(&str, bool)>> {
+        self.original
+            .get("$vocabulary")
+            .and_then(|v| v.as_object())
+            .map(|v| {
+                v.iter()
+                    .map(|(uri, required)| (uri.as_str(), required.as_bool().unwrap_or(false)))
+                    .collect()
+            })
+    }
+
+    /// Whether this schema sets `$recursiveAnchor` (draft 2019-09 only).
+    pub fn has_recursive_anchor(&self) -> bool {
+        self.version == SchemaVersion::Draft2019_09
+            && self
+                .original
+                .get("$recursiveAnchor")
+                .and_then(|a| a.as_bool())
+                == Some(true)
+    }
+
+    /// Whether this schema declares the given `$dynamicAnchor`.
+    pub fn has_dynamic_anchor(&self, anchor: &str) -> bool {
+        self.version >= SchemaVersion::Draft2020_12
+            && self.original.get("$dynamicAnchor").and_then(|a| a.as_str()) == Some(anchor)
+    }
+
+    /// The annotations of this schema, evaluated at the given keyword location.
+    fn annotations(&self, location: &str) -> Vec<validators::Annotation> {
+        self.annotation_keywords
+            .iter()
+            .map(|keyword| validators::Annotation {
+                keyword: keyword.to_string(),
+                value: self.original[*keyword].clone(),
+                keyword_location: format!("{location}/{keyword}"),
+                schema_url: format!("{}/{}", self.location, keyword),
+            })
+            .collect()
+    }
+
     fn validate_in_scope(
         &self,
         data: &Value,
         path: &str,
-        scope: &scope::Scope,
+        ctx: &ValidationContext<'_>,
     ) -> validators::ValidationState {
+        // the outermost schema keeps the budget of the whole validation, an aborted
+        // validation fails with just the error that aborted it
+        let budget;
+        let ctx = match ctx.budget {
+            Some(_) => ctx,
+            None => {
+                budget = Budget::default();
+                let state = self.validate_in_scope(data, path, &ctx.with_budget(&budget));
+                return match budget.aborted.into_inner() {
+                    Some(error) => {
+                        if let Some(recorder) = ctx.recorder {
+                            recorder.abort(&*error);
+                        }
+                        let mut state = validators::ValidationState::new();
+                        state.errors.push(error);
+                        state
+                    }
+                    None => state,
+                };
+            }
+        };
+        if ctx.is_aborted() {
+            return validators::ValidationState::new();
+        }
+        let depth = match ctx.enter_schema(self, path) {
+            Ok(depth) => depth,
+            Err(error) => {
+                *ctx.budget.unwrap().aborted.borrow_mut() = Some(error);
+                return validators::ValidationState::new();
+            }
+        };
+
         let mut state = validators::ValidationState::new();
         let mut data = Cow::Borrowed(data);
 
+        // entering another schema resource (either directly or through a reference)
+        // extends the dynamic scope
+        let entered;
+        let ctx = if ctx.resource != Some(&self.resource) {
+            entered = ctx.enter(&self.resource);
+            &entered
+        } else {
+            ctx
+        };
+
+        let mut location = None;
+        let mut keywords = vec![];
         for validator in self.validators.iter() {
-            let mut result = validator.validate(&data, path, scope, &state);
+            let outer = ctx.recorder.map(|recorder| recorder.begin_keyword());
+            let mut result = validator.validator.validate(
+                &data,
+                path,
+                &ctx.enter_keyword(self, validator, path, depth),
+                &state,
+            );
+
+            // the errors that the keyword produced itself, rather than its subschemas
+            let own: Vec<usize> = (0..result.errors.len())
+                .filter(|idx| result.errors[*idx].get_keyword_location().is_none())
+                .collect();
+            if !own.is_empty() || ctx.recorder.is_some() {
+                let location = location.get_or_insert_with(|| ctx.keyword_location(self));
+                let keyword_location = format!("{}/{}", location, validator.keyword);
+                for idx in own.iter() {
+                    let error = &mut result.errors[*idx];
+                    if error.get_schema_path().is_none() {
+                        error.set_schema_location(
+                            validator.location.path.clone(),
+                            validator.location.url.clone(),
+                        );
+                    }
+                    // validators of several keywords name the actual one in the schema path
+                    let keyword_path = error
+                        .get_schema_path()
+                        .and_then(|schema_path| schema_path.strip_prefix(self.pointer.as_str()))
+                        .filter(|rest| rest.starts_with('/'))
+                        .map(|rest| format!("{location}{rest}"));
+                    error.set_keyword_location(
+                        keyword_path.unwrap_or_else(|| keyword_location.clone()),
+                    );
+                }
+                if let (Some(recorder), Some(outer)) = (ctx.recorder, outer) {
+                    keywords.push(recorder.end_keyword(
+                        outer,
+                        keyword_location,
+                        validator.location.url.clone(),
+                        path,
+                        &result,
+                        &own,
+                    ));
+                }
+            }
+
             if result.is_valid() && result.replacement.is_some() {
                 *data.to_mut() = result.replacement.take().unwrap();
             }
             state.append(result);
+            if ctx.should_stop(&state) {
+                break;
+            }
+        }
+
+        if !state.is_valid() {
+            state.annotations.clear();
+        } else if ctx.scope.collect_annotations {
+            let location = location.get_or_insert_with(|| ctx.keyword_location(self));
+            let mut annotations = self.annotations(location);
+            if !annotations.is_empty() {
+                annotations.extend(state.annotations.remove(path).unwrap_or_default());
+                state.annotations.insert(path.to_string(), annotations);
+            }
+        }
+
+        if let Some(recorder) = ctx.recorder {
+            recorder.add_schema(
+                location.unwrap_or_else(|| ctx.keyword_location(self)),
+                self.location.clone(),
+                path,
+                state.is_valid(),
+                keywords,
+            );
         }
 
         state.set_replacement(data);
@@ -586,10 +1642,143 @@ impl Schema {
     }
 }
 
+/// The keywords that are collected by `Scope::collect_annotations`.
+const ANNOTATION_KEYWORDS: [&str; 11] = [
+    "title",
+    "description",
+    "default",
+    "examples",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+    "format",
+    "contentMediaType",
+    "contentEncoding",
+    "contentSchema",
+];
+
+/// The annotation keywords of a schema that are collected, i.e. those of the vocabularies
+/// declared by its metaschema.
+fn annotation_keywords(def: &Value, context: &WalkContext) -> Vec<&'static str> {
+    // before draft 2019-09 all keywords next to "$ref" are ignored
+    if context.version < SchemaVersion::Draft2019_09 && def.get("$ref").is_some() {
+        return vec![];
+    }
+    ANNOTATION_KEYWORDS
+        .iter()
+        .copied()
+        .filter(|keyword| def.get(*keyword).is_some())
+        .filter(|keyword| match context.vocabularies {
+            Some(enabled) => {
+                let vocabularies = keywords::vocabularies(keyword, context.version);
+                vocabularies.is_empty() || vocabularies.iter().any(|v| enabled.contains(*v))
+            }
+            None => true,
+        })
+        .collect()
+}
+
+/// Whether `pointer` points to the value of `key` or below it.
+fn is_below(pointer: &str, key: &str) -> bool {
+    pointer
+        .strip_prefix('/')
+        .and_then(|rest| rest.strip_prefix(key))
+        .map(|rest| rest.is_empty() || rest.starts_with('/'))
+        .unwrap_or(false)
+}
+
+/// Whether an id identifies a schema resource rather than a location inside one.
+fn is_resource_url(url: &Url) -> bool {
+    url.fragment().map(|f| f.is_empty()).unwrap_or(true)
+}
+
+/// Wraps an unknown key or a malformed keyword into `SchemaError::Located` with the
+/// pointer to the keyword. The line and column are filled
+/// in by `Scope::compile_source`, the location is dropped for everybody else, see
+/// `compile`.
+fn locate_keyword(error: SchemaError, context: &WalkContext, keyword: &str) -> SchemaError {
+    match error {
+        SchemaError::UnknownKey(_) | SchemaError::Malformed { .. } => {}
+        error => return error,
+    }
+    SchemaError::Located {
+        location: Box::new(SourceLocation {
+            url: None,
+            pointer: format!(
+                "{}/{}",
+                context.pointer(),
+                keyword.replace('~', "~0").replace('/', "~1")
+            ),
+            line: 0,
+            column: 0,
+        }),
+        error: Box::new(error),
+    }
+}
+
+/// Parses the id of a subschema against the base URL of the enclosing resource.
+fn parse_id(def: &Value, version: SchemaVersion, base: &Url) -> Result<Option<Url>, SchemaError> {
+    // before draft 2019-09 all keywords next to "$ref" are ignored, including the id
+    if version < SchemaVersion::Draft2019_09 && def.get("$ref").is_some() {
+        Ok(None)
+    } else {
+        helpers::parse_url_key_with_base(helpers::id_key(version), def, base)
+    }
+}
+
+/// Collects the plain-name anchors (`$anchor` and `$dynamicAnchor`) declared by a schema.
+fn parse_anchors(def: &Value, version: SchemaVersion) -> Result<Vec<&str>, SchemaError> {
+    let mut anchors = vec![];
+    let mut keys = vec![];
+    if version >= SchemaVersion::Draft2019_09 {
+        keys.push("$anchor");
+    }
+    if version >= SchemaVersion::Draft2020_12 {
+        keys.push("$dynamicAnchor");
+    }
+    for key in keys {
+        if let Some(anchor) = def.get(key) {
+            anchors.push(anchor.as_str().ok_or_else(|| SchemaError::Malformed {
+                path: "".to_string(),
+                detail: format!("{} must be a string", key),
+            })?);
+        }
+    }
+    Ok(anchors)
+}
+
+/// Collects the targets of the reference keywords of a schema, resolved against `base`.
+///
+/// Malformed references are skipped here, they are reported by the keywords themselves.
+fn parse_references(def: &Value, version: SchemaVersion, base: &Url) -> Vec<Url> {
+    let mut keys = vec!["$ref"];
+    if version == SchemaVersion::Draft2019_09 {
+        keys.push("$recursiveRef");
+    }
+    if version >= SchemaVersion::Draft2020_12 {
+        keys.push("$dynamicRef");
+    }
+    keys.into_iter()
+        .filter_map(|key| def.get(key).and_then(|value| value.as_str()))
+        .filter_map(|reference| base.join(reference).ok())
+        .collect()
+}
+
 pub fn compile(
     def: Value,
     external_id: Option<Url>,
     settings: CompilationSettings<'_>,
+) -> Result<Schema, SchemaError> {
+    compile_located(def, external_id, settings).map_err(SchemaError::unlocated)
+}
+
+/// The same as `compile`, but an unknown key or a malformed keyword comes wrapped in
+/// `SchemaError::Located` with the pointer to the keyword, for the source location to be
+/// filled in.
+pub(crate) fn compile_located(
+    def: Value,
+    external_id: Option<Url>,
+    settings: CompilationSettings<'_>,
 ) -> Result<Schema, SchemaError> {
     Schema::compile(def, external_id, settings)
 }
@@ -613,3 +1802,19 @@ fn schema_compiles_boolean_schema() {
     )
     .is_ok());
 }
+
+#[test]
+fn schema_bans_id_after_draft4() {
+    let compile = |version| {
+        compile(
+            json!({ "id": "http://example.com/schema", "type": "string" }),
+            None,
+            CompilationSettings::new(&keywords::default(), true, version),
+        )
+    };
+    assert!(compile(SchemaVersion::Draft4).is_ok());
+    assert!(matches!(
+        compile(SchemaVersion::Draft6),
+        Err(SchemaError::UnknownKey(..))
+    ));
+}
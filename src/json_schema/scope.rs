@@ -1,18 +1,50 @@
 use serde_json::Value;
 use std::collections;
+use std::fs;
+use std::path;
 
+use super::bundle;
+use super::dereference::{self, RecursiveRefs};
 use super::helpers;
 use super::keywords;
+use super::lint;
+use super::metaschemas;
+use super::report;
+use super::resolver;
 use super::schema;
+use super::validators;
 use super::SchemaVersion;
 
+/// A reference that cannot be resolved within a scope, see `Scope::check_references`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DanglingReference {
+    /// The id of the compiled document that contains the reference.
+    pub document: url::Url,
+    /// The JSON pointer of the referencing schema within the document.
+    pub schema_path: String,
+    /// The unresolvable target.
+    pub reference: url::Url,
+}
+
 #[allow(dead_code)]
 #[derive(Debug)]
 pub struct Scope {
     keywords: keywords::KeywordMap,
     schemes: collections::HashMap<String, schema::Schema>,
     pub(crate) supply_defaults: bool,
+    check_defaults: bool,
+    pub(crate) collect_annotations: bool,
+    pub(crate) collapse_errors: bool,
+    pub(crate) detect_discriminators: bool,
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) max_steps: Option<usize>,
     schema_version: SchemaVersion,
+    fallback_version: Option<SchemaVersion>,
+    resolvers: Vec<Box<dyn resolver::SchemaResolver>>,
+    metaschemas: Option<&'static Scope>,
+    /// Whether the standard metaschemas are found in this scope without being compiled
+    /// into it, which is only off for the scope that holds them.
+    pub(crate) bundled_metaschemas: bool,
 }
 
 #[allow(dead_code)]
@@ -28,7 +60,17 @@ impl Scope {
             keywords: keywords::default(),
             schemes: collections::HashMap::new(),
             supply_defaults: false,
+            check_defaults: false,
+            collect_annotations: false,
+            collapse_errors: false,
+            detect_discriminators: false,
+            max_depth: None,
+            max_steps: None,
             schema_version: version,
+            fallback_version: None,
+            resolvers: vec![],
+            metaschemas: None,
+            bundled_metaschemas: true,
         }
     }
 
@@ -44,11 +86,24 @@ impl Scope {
         scope
     }
 
+    /// Sets the version used for schemas that do not declare one.
+    ///
+    /// Schemas that reference a standard metaschema through `$schema` are compiled
+    /// according to that draft, and so are schemas whose metaschema is known to this
+    /// scope. This applies to every schema resource, so documents of different drafts
+    /// can be mixed in one scope and even embedded into each other.
     pub fn set_version(mut self, version: SchemaVersion) -> Self {
         self.schema_version = version;
         self
     }
 
+    /// Sets the version used for schemas whose `$schema` refers to an unknown
+    /// metaschema. By default the version set with `set_version` is used.
+    pub fn set_fallback_version(mut self, version: SchemaVersion) -> Self {
+        self.fallback_version = Some(version);
+        self
+    }
+
     /// ### use `default` values to compute an enriched version of the input
     ///
     /// JSON schema foresees the `default` attribute in any schema but does not assign
@@ -117,26 +172,108 @@ impl Scope {
             keywords: self.keywords,
             schemes: self.schemes,
             supply_defaults: true,
-            schema_version: SchemaVersion::Draft7,
+            check_defaults: self.check_defaults,
+            collect_annotations: self.collect_annotations,
+            collapse_errors: self.collapse_errors,
+            detect_discriminators: self.detect_discriminators,
+            max_depth: self.max_depth,
+            max_steps: self.max_steps,
+            schema_version: self.schema_version,
+            fallback_version: self.fallback_version,
+            resolvers: self.resolvers,
+            metaschemas: self.metaschemas,
+            bundled_metaschemas: self.bundled_metaschemas,
         }
     }
 
+    /// Validates every `default` and each entry of `examples` against the schema that
+    /// declares it when a document is compiled, and fails with `SchemaError::Malformed`
+    /// at the JSON pointer of the first value that does not match.
+    ///
+    /// Without this check a wrong default is injected into instances as is when
+    /// `supply_defaults` is on, and only fails the validation of those.
+    #[must_use]
+    pub fn validate_defaults(mut self) -> Self {
+        self.check_defaults = true;
+        self
+    }
+
+    /// Collects the annotation keywords (`title`, `description`, `default`, `examples`,
+    /// `deprecated`, `readOnly`, `writeOnly`, `format` and the content keywords) while
+    /// validating, see `ValidationState::annotations`.
+    ///
+    /// Only the annotations of schemas that validate their instance are kept, so e.g.
+    /// a failed branch of `anyOf` or a subschema of `not` contributes none.
+    #[must_use]
+    pub fn collect_annotations(mut self) -> Self {
+        self.collect_annotations = true;
+        self
+    }
+
+    /// Keeps only the most relevant failed branch in the errors of `anyOf` and `oneOf`,
+    /// as picked by `ValidationState::best_error`, instead of all of them.
+    ///
+    /// With unions of many variants the errors of the other branches otherwise bury the
+    /// one that explains the failure.
+    #[must_use]
+    pub fn collapse_applicator_errors(mut self) -> Self {
+        self.collapse_errors = true;
+        self
+    }
+
+    /// Treats a property as the discriminator of an `anyOf` or `oneOf` without an
+    /// OpenAPI `discriminator` if every branch requires certain values for it through
+    /// `const` or `enum`, so that only the branches allowing its value are validated.
+    ///
+    /// A value that no branch allows then fails with `errors::UnknownDiscriminator`
+    /// instead of the errors of every branch.
+    #[must_use]
+    pub fn detect_discriminators(mut self) -> Self {
+        self.detect_discriminators = true;
+        self
+    }
+
+    /// Stops validating instances that are nested deeper than `depth` levels below the
+    /// validated value, failing with `errors::MaxDepth` instead.
+    ///
+    /// Deeply nested instances otherwise validate recursively until the stack overflows,
+    /// so this should be set when validating untrusted data against recursive schemas.
+    #[must_use]
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Stops a validation after `steps` schemas have been evaluated, failing with
+    /// `errors::MaxSteps` instead.
+    ///
+    /// This bounds the time spent on a single instance, e.g. for schemas that combine
+    /// many applicators over large arrays.
+    #[must_use]
+    pub fn max_steps(mut self, steps: usize) -> Self {
+        self.max_steps = Some(steps);
+        self
+    }
+
+    /// Validates every document against its metaschema before it is compiled, and
+    /// fails with `SchemaError::InvalidSchema` listing the violations otherwise.
+    ///
+    /// The standard metaschemas of all drafts are bundled. A document whose `$schema`
+    /// refers to a custom metaschema is validated against that one if it is known to this
+    /// scope (possibly through a resolver), and against the standard metaschema of the
+    /// version it is compiled with otherwise.
+    #[must_use]
+    pub fn validate_against_metaschema(mut self) -> Self {
+        self.metaschemas = Some(metaschemas::shared());
+        self
+    }
+
     pub fn compile(
         &mut self,
         def: Value,
         ban_unknown: bool,
     ) -> Result<url::Url, schema::SchemaError> {
-        let mut schema = schema::compile(
-            def,
-            None,
-            schema::CompilationSettings::new(&self.keywords, ban_unknown, self.schema_version),
-        )?;
-        let id = schema.id.clone().unwrap();
-        if self.supply_defaults {
-            schema.add_defaults(&id, self);
-        }
-        self.add(&id, schema)?;
-        Ok(id)
+        self.compile_document(def, None, ban_unknown, None)
     }
 
     pub fn compile_with_id(
@@ -145,15 +282,8 @@ impl Scope {
         def: Value,
         ban_unknown: bool,
     ) -> Result<(), schema::SchemaError> {
-        let mut schema = schema::compile(
-            def,
-            Some(id.clone()),
-            schema::CompilationSettings::new(&self.keywords, ban_unknown, self.schema_version),
-        )?;
-        if self.supply_defaults {
-            schema.add_defaults(id, self);
-        }
-        self.add(id, schema)
+        self.compile_document(def, Some(id.clone()), ban_unknown, None)
+            .map(|_| ())
     }
 
     pub fn compile_and_return(
@@ -161,16 +291,8 @@ impl Scope {
         def: Value,
         ban_unknown: bool,
     ) -> Result<schema::ScopedSchema<'_>, schema::SchemaError> {
-        let mut schema = schema::compile(
-            def,
-            None,
-            schema::CompilationSettings::new(&self.keywords, ban_unknown, self.schema_version),
-        )?;
-        let id = schema.id.clone().unwrap();
-        if self.supply_defaults {
-            schema.add_defaults(&id, self);
-        }
-        self.add_and_return(&id, schema)
+        let id = self.compile_document(def, None, ban_unknown, None)?;
+        Ok(self.get_document(&id))
     }
 
     pub fn compile_and_return_with_id<'a>(
@@ -179,15 +301,341 @@ impl Scope {
         def: Value,
         ban_unknown: bool,
     ) -> Result<schema::ScopedSchema<'a>, schema::SchemaError> {
-        let mut schema = schema::compile(
-            def,
-            Some(id.clone()),
-            schema::CompilationSettings::new(&self.keywords, ban_unknown, self.schema_version),
-        )?;
+        let id = self.compile_document(def, Some(id.clone()), ban_unknown, None)?;
+        Ok(self.get_document(&id))
+    }
+
+    /// Compiles a document from its JSON text, like `compile`. Errors in the document are
+    /// wrapped in `SchemaError::Located`, with the line and column of the offending value,
+    /// or of the name of an unknown keyword.
+    pub fn compile_source(
+        &mut self,
+        source: &str,
+        ban_unknown: bool,
+    ) -> Result<url::Url, schema::SchemaError> {
+        let def = parse_source(source, None)?;
+        self.compile_document(def, None, ban_unknown, Some(source))
+    }
+
+    /// Compiles a document from its JSON text, like `compile_with_id`. The location of an
+    /// error also names the file of a `file:` id, or the id itself.
+    pub fn compile_source_with_id(
+        &mut self,
+        id: &url::Url,
+        source: &str,
+        ban_unknown: bool,
+    ) -> Result<(), schema::SchemaError> {
+        let def = parse_source(source, Some(id))?;
+        self.compile_document(def, Some(id.clone()), ban_unknown, Some(source))
+            .map(|_| ())
+    }
+
+    /// Bundles the document with the given id and all documents it references, directly
+    /// or indirectly, into a single document, e.g. for tools that cannot resolve references
+    /// to other documents.
+    ///
+    /// The referenced documents are embedded under `$defs` (`definitions` before draft
+    /// 2019-09), named after the last segment of their URL, and every `$ref` is rewritten to
+    /// the JSON pointer of its target within the bundle, so that the bundle validates like
+    /// the original. As a consequence, the embedded schemas lose their `$id` and anchors.
+    /// Documents that use `$recursiveRef` or `$dynamicRef`, or that are written for
+    /// another draft than the bundled one, cannot be bundled.
+    pub fn bundle(&self, id: &url::Url) -> Result<Value, schema::SchemaError> {
+        bundle::bundle(self, id)
+    }
+
+    /// Returns the schema with the given id with the target of every `$ref` inlined, e.g.
+    /// for tools that cannot follow references.
+    ///
+    /// Before draft 2019-09 a `$ref` replaces its schema, later the target is added to
+    /// `allOf` if the schema has other keywords. References that would have to be inlined
+    /// into their own target are kept or rejected, depending on `recursive`. `$recursiveRef`
+    /// and `$dynamicRef` are left as they are.
+    pub fn dereference(
+        &self,
+        id: &url::Url,
+        recursive: RecursiveRefs,
+    ) -> Result<Value, schema::SchemaError> {
+        dereference::dereference(self, id, recursive)
+    }
+
+    /// Compiles every `.json` file below `dir`, e.g. a directory of schemas that refer to
+    /// each other with relative references like `"../common/address.json#/definitions/zip"`.
+    ///
+    /// The id of each file is its path relative to `dir`, resolved against `base_uri`,
+    /// so `common/address.json` becomes `http://example.com/schemas/common/address.json`
+    /// for the base URI `http://example.com/schemas/`. A `$id` at the root of a file does
+    /// not change it. The files can be referenced before they are compiled, so they are
+    /// compiled in any order. Returns the ids of the files, sorted by path.
+    ///
+    /// Errors in a file are located in its text like with `compile_source_with_id`.
+    pub fn load_dir<P: AsRef<path::Path>>(
+        &mut self,
+        dir: P,
+        base_uri: &url::Url,
+        ban_unknown: bool,
+    ) -> Result<Vec<url::Url>, schema::SchemaError> {
+        let dir = dir.as_ref();
+        let mut base_uri = base_uri.clone();
+        if !base_uri.path().ends_with('/') {
+            base_uri.set_path(&format!("{}/", base_uri.path()));
+        }
+
+        let mut files = vec![];
+        list_json_files(dir, path::Path::new(""), &mut files)?;
+        files.sort();
+        let mut documents = vec![];
+        for file in files {
+            let relative = file
+                .iter()
+                .map(|part| part.to_str())
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| schema::SchemaError::File {
+                    path: dir.join(&file),
+                    detail: "The file name is not valid UTF-8.".to_string(),
+                })?;
+            let id = base_uri
+                .join(&relative.join("/"))
+                .map_err(schema::SchemaError::UrlParseError)?;
+            if self.resolve(&id).is_some() {
+                return Err(schema::SchemaError::IdConflicts);
+            }
+            documents.push((dir.join(file), id));
+        }
+
+        // the files that are referenced by the one being compiled are compiled with it
+        let known: collections::HashSet<String> = self.schemes.keys().cloned().collect();
+        self.resolvers.insert(
+            0,
+            Box::new(resolver::DirectoryResolver::new().map(base_uri.as_str(), dir)),
+        );
+        let mut result = Ok(());
+        for (file, id) in documents.iter() {
+            if self.resolve(id).is_some() {
+                continue;
+            }
+            result = resolver::read_source(file, id)
+                .and_then(|source| {
+                    let document = parse_source(&source, Some(id))?;
+                    self.compile_document(document, Some(id.clone()), ban_unknown, Some(&source))
+                })
+                .map(|_| ());
+            if result.is_err() {
+                break;
+            }
+        }
+        self.resolvers.remove(0);
+
+        // the directory is loaded completely or not at all, so that it can be retried
+        if result.is_err() {
+            let added = self
+                .schemes
+                .keys()
+                .filter(|key| !known.contains(*key))
+                .cloned()
+                .collect();
+            self.remove_documents(&added);
+        }
+        result.map(|_| documents.into_iter().map(|(_, id)| id).collect())
+    }
+
+    /// Compiles a document and adds it to the scope, together with every referenced
+    /// document that is unknown to the scope and can be obtained from the resolvers.
+    fn compile_document(
+        &mut self,
+        def: Value,
+        id: Option<url::Url>,
+        ban_unknown: bool,
+        source: Option<&str>,
+    ) -> Result<url::Url, schema::SchemaError> {
+        // only the errors in the document itself are located in its source text
+        let url = id.clone();
+        let locate = |error| match source {
+            Some(source) => locate_error(error, source, url.as_ref()),
+            None => error.unlocated(),
+        };
+        self.load_metaschema(&def, id.as_ref()).map_err(locate)?;
+        let schema = self.compile_schema(def, id, ban_unknown).map_err(locate)?;
+        let id = schema.id.clone().unwrap();
+        self.add(&id, schema).map_err(locate)?;
+
+        // nothing of the document is kept if it, or a document it references, fails
+        let mut compiled = vec![id.clone()];
+        let result = self
+            .compile_references(&mut compiled, ban_unknown)
+            .and_then(|()| {
+                if !self.check_defaults {
+                    return Ok(());
+                }
+                // the defaults are validated with the references of the documents linked
+                self.link();
+                compiled.iter().enumerate().try_for_each(|(idx, id)| {
+                    let key = helpers::serialize_schema_path(id).0;
+                    check_defaults(self, &self.schemes[&key]).map_err(|error| {
+                        if idx == 0 {
+                            locate(error)
+                        } else {
+                            error
+                        }
+                    })
+                })
+            });
+        if let Err(error) = result {
+            let removed: collections::HashSet<String> = compiled
+                .iter()
+                .map(|id| helpers::serialize_schema_path(id).0)
+                .collect();
+            self.remove_documents(&removed);
+            return Err(error);
+        }
+
+        // defaults may be taken from referenced documents, so these are enriched first
         if self.supply_defaults {
-            schema.add_defaults(id, self);
+            for id in compiled.iter().rev() {
+                let key = helpers::serialize_schema_path(id).0;
+                let mut schema = self.schemes.remove(&key).unwrap();
+                schema.add_defaults(id, self);
+                self.schemes.insert(key, schema);
+            }
+        }
+
+        self.link();
+        Ok(id)
+    }
+
+    /// Fetches and compiles the unknown documents referenced by the `compiled` ones,
+    /// transitively, and appends their ids to `compiled`. Unknown keywords are banned in
+    /// them if they are in the document that references them.
+    fn compile_references(
+        &mut self,
+        compiled: &mut Vec<url::Url>,
+        ban_unknown: bool,
+    ) -> Result<(), schema::SchemaError> {
+        let mut idx = 0;
+        while idx < compiled.len() {
+            let key = helpers::serialize_schema_path(&compiled[idx]).0;
+            let references: Vec<url::Url> = self.schemes[&key]
+                .references()
+                .into_iter()
+                .map(|(_, url)| url.clone())
+                .collect();
+            idx += 1;
+
+            for mut url in references {
+                url.set_fragment(None);
+                if self.resolve(&url).is_some() {
+                    continue;
+                }
+                if let Some(document) = self.fetch(&url)? {
+                    self.load_metaschema(&document, Some(&url))?;
+                    // only the errors in the document being compiled are located
+                    let schema = self
+                        .compile_schema(document, Some(url.clone()), ban_unknown)
+                        .map_err(schema::SchemaError::unlocated)?;
+                    self.add(&url, schema)?;
+                    compiled.push(url);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn compile_schema(
+        &self,
+        def: Value,
+        id: Option<url::Url>,
+        ban_unknown: bool,
+    ) -> Result<schema::Schema, schema::SchemaError> {
+        let settings = self.compilation_settings(ban_unknown);
+        if let Some(metaschemas) = self.metaschemas {
+            let url = match helpers::parse_url_key("$schema", &def)? {
+                Some(url) if SchemaVersion::from_metaschema_url(&url).is_none() => {
+                    Some(url).filter(|url| self.resolve(url).is_some())
+                }
+                _ => None,
+            };
+            let (url, metaschema) = match url {
+                Some(url) => {
+                    let metaschema = self.resolve(&url).unwrap();
+                    (url, metaschema)
+                }
+                None => {
+                    let version = settings.detect_version(&def, settings.schema_version)?;
+                    let url = version.metaschema_url();
+                    let metaschema = metaschemas.resolve(&url).unwrap();
+                    (url, metaschema)
+                }
+            };
+            let state = metaschema.validate(&def);
+            if !state.is_valid() {
+                return Err(schema::SchemaError::InvalidSchema {
+                    metaschema: url,
+                    violations: state
+                        .errors
+                        .iter()
+                        .map(|error| schema::MetaschemaViolation {
+                            pointer: error.get_path().to_string(),
+                            code: error.get_code().to_string(),
+                            title: error.get_title().to_string(),
+                            detail: error.get_detail().map(|detail| detail.to_string()),
+                        })
+                        .collect(),
+                });
+            }
         }
-        self.add_and_return(id, schema)
+        schema::compile_located(def, id, settings)
+    }
+
+    /// Loads the custom metaschema of a document through the resolvers, so that the
+    /// version and vocabularies of the document can be derived from it.
+    fn load_metaschema(
+        &mut self,
+        def: &Value,
+        id: Option<&url::Url>,
+    ) -> Result<(), schema::SchemaError> {
+        if self.resolvers.is_empty() {
+            return Ok(());
+        }
+        let mut url = match def.get("$schema").and_then(|url| url.as_str()) {
+            Some(url) => match url::Url::parse(url) {
+                Ok(url) => url,
+                Err(_) => return Ok(()),
+            },
+            None => return Ok(()),
+        };
+        url.set_fragment(None);
+        if SchemaVersion::from_metaschema_url(&url).is_some()
+            || id == Some(&url)
+            || self.resolve(&url).is_some()
+        {
+            return Ok(());
+        }
+        if let Some(document) = self.fetch(&url)? {
+            self.compile_document(document, Some(url), false, None)?;
+        }
+        Ok(())
+    }
+
+    fn fetch(&self, url: &url::Url) -> Result<Option<Value>, schema::SchemaError> {
+        for resolver in self.resolvers.iter() {
+            if let Some(document) = resolver.resolve(url)? {
+                return Ok(Some(document));
+            }
+        }
+        Ok(None)
+    }
+
+    fn get_document(&self, id: &url::Url) -> schema::ScopedSchema<'_> {
+        let key = helpers::serialize_schema_path(id).0;
+        schema::ScopedSchema::new(self, &self.schemes[&key])
+    }
+
+    fn compilation_settings(&self, ban_unknown: bool) -> schema::CompilationSettings<'_> {
+        let mut settings =
+            schema::CompilationSettings::new(&self.keywords, ban_unknown, self.schema_version);
+        settings.fallback_version = self.fallback_version;
+        settings.metaschemas = Some(self);
+        settings
     }
 
     pub fn add_keyword<T>(&mut self, keys: Vec<&'static str>, keyword: T)
@@ -197,6 +645,15 @@ impl Scope {
         keywords::decouple_keyword((keys, Box::new(keyword)), &mut self.keywords);
     }
 
+    /// Registers a resolver that provides referenced documents which have not been
+    /// compiled into this scope. Resolvers are asked in the order of registration.
+    pub fn add_resolver<R>(&mut self, resolver: R)
+    where
+        R: resolver::SchemaResolver + 'static,
+    {
+        self.resolvers.push(Box::new(resolver));
+    }
+
     #[allow(clippy::map_entry)] // allowing for the return values
     fn add(&mut self, id: &url::Url, schema: schema::Schema) -> Result<(), schema::SchemaError> {
         let (id_str, fragment) = helpers::serialize_schema_path(id);
@@ -213,48 +670,295 @@ impl Scope {
         }
     }
 
-    #[allow(clippy::map_entry)] // allowing for the return values
-    fn add_and_return<'a>(
-        &'a mut self,
-        id: &url::Url,
-        schema: schema::Schema,
-    ) -> Result<schema::ScopedSchema<'a>, schema::SchemaError> {
-        let (id_str, fragment) = helpers::serialize_schema_path(id);
+    /// Resolves the targets of all references and subschemas of the compiled schemas
+    /// and returns those that cannot be found in this scope.
+    ///
+    /// Such references are otherwise only reported during validation, in
+    /// `ValidationState::missing`, and only when the referencing schema is visited.
+    pub fn check_references(&self) -> Vec<DanglingReference> {
+        let mut dangling = vec![];
+        for schema in self.schemes.values() {
+            let document = schema.id.as_ref().unwrap();
+            for (schema_path, reference) in schema.validator_references() {
+                if self.resolve(reference).is_none() {
+                    dangling.push(DanglingReference {
+                        document: document.clone(),
+                        schema_path,
+                        reference: reference.clone(),
+                    });
+                }
+            }
+        }
+        dangling.sort();
+        dangling.dedup();
+        dangling
+    }
 
-        if fragment.is_some() {
-            return Err(schema::SchemaError::WrongId);
+    /// Looks for mistakes in the compiled schemas that do not prevent them from compiling,
+    /// such as `minimum` greater than `maximum`, keywords for another type than the one
+    /// allowed by `type`, required properties that `additionalProperties: false` forbids,
+    /// defaults or examples that do not match their schema or `if` without `then` and
+    /// `else`.
+    pub fn lint(&self) -> Vec<lint::Warning> {
+        let mut warnings = vec![];
+        for schema in self.schemes.values() {
+            let document = schema.id.as_ref().unwrap();
+            warnings.extend(lint::lint_document(self, &self.keywords, document, schema));
         }
+        warnings.sort();
+        warnings
+    }
 
-        if !self.schemes.contains_key(&id_str) {
-            self.schemes.insert(id_str.clone(), schema);
-            Ok(schema::ScopedSchema::new(self, &self.schemes[&id_str]))
+    pub fn resolve<'a>(&'a self, id: &url::Url) -> Option<schema::ScopedSchema<'a>> {
+        self.find(id)
+            .map(|(_, schema)| schema::ScopedSchema::new(self, schema))
+    }
+
+    /// Returns the compiled document with the given key, see `find`.
+    pub(crate) fn document(&self, key: &str) -> Option<&schema::Schema> {
+        self.schemes
+            .get(key)
+            .or_else(|| self.bundled().and_then(|scope| scope.document(key)))
+    }
+
+    /// The scope with the standard metaschemas, which are looked up there if they are
+    /// not compiled into this scope.
+    fn bundled(&self) -> Option<&'static Scope> {
+        if self.bundled_metaschemas {
+            Some(metaschemas::shared())
         } else {
-            Err(schema::SchemaError::IdConflicts)
+            None
         }
     }
 
-    pub fn resolve<'a>(&'a self, id: &url::Url) -> Option<schema::ScopedSchema<'a>> {
+    /// Finds a schema together with the key of the document that contains it.
+    pub(crate) fn find(&self, id: &url::Url) -> Option<(&String, &schema::Schema)> {
         let (schema_path, fragment) = helpers::serialize_schema_path(id);
 
-        let schema = self.schemes.get(&schema_path).or_else(|| {
+        let document = self.schemes.get_key_value(&schema_path).or_else(|| {
             // Searching for inline schema in O(N)
-            for (_, schema) in self.schemes.iter() {
+            for (document, schema) in self.schemes.iter() {
                 let internal_schema = schema.resolve(schema_path.as_ref());
-                if internal_schema.is_some() {
-                    return internal_schema;
+                if let Some(internal_schema) = internal_schema {
+                    return Some((document, internal_schema));
                 }
             }
 
             None
         });
+        let (document, schema) = match (document, self.bundled()) {
+            (Some(found), _) => found,
+            (None, Some(bundled)) => return bundled.find(id),
+            (None, None) => return None,
+        };
 
-        schema.and_then(|schema| match fragment {
+        match fragment {
             Some(ref fragment) => schema
                 .resolve_fragment(fragment)
-                .map(|schema| schema::ScopedSchema::new(self, schema)),
-            None => Some(schema::ScopedSchema::new(self, schema)),
-        })
+                .map(|schema| (document, schema)),
+            None => Some((document, schema)),
+        }
+    }
+
+    /// Links the references of all validators that are not linked yet to their targets,
+    /// so that validation does not have to resolve their URLs.
+    ///
+    /// Documents are never replaced in a scope, so existing links stay valid.
+    fn link(&mut self) {
+        let mut links: collections::HashMap<url::Url, Option<schema::Link>> =
+            collections::HashMap::new();
+        for document in self.schemes.values() {
+            document.visit_references(&mut |reference| {
+                if !reference.is_linked() && !links.contains_key(&reference.url) {
+                    let link = self
+                        .find(&reference.url)
+                        .map(|(document, schema)| schema::Link {
+                            document: document.clone(),
+                            path: schema.tree_path(),
+                        });
+                    links.insert(reference.url.clone(), link);
+                }
+            });
+        }
+
+        for document in self.schemes.values_mut() {
+            document.visit_references_mut(&mut |reference| {
+                if let Some(Some(link)) = links.get(&reference.url) {
+                    if !reference.is_linked() {
+                        reference.set_link(link.clone());
+                    }
+                }
+            });
+        }
+        self.link_validators();
+    }
+
+    /// Lets the validators derive what they need from the targets of their references,
+    /// like `link` in two passes: the selectors are collected in the order the
+    /// validators are visited, and handed back in the same order.
+    fn link_validators(&mut self) {
+        let mut selectors: collections::HashMap<String, Vec<Option<validators::Selector>>> =
+            collections::HashMap::new();
+        for (key, document) in self.schemes.iter() {
+            let mut collected = vec![];
+            document.visit_validators(&mut |validator| collected.push(validator.selector(self)));
+            selectors.insert(key.clone(), collected);
+        }
+
+        for (key, document) in self.schemes.iter_mut() {
+            let mut selectors = selectors.remove(key).unwrap_or_default().into_iter();
+            document
+                .visit_validators_mut(&mut |validator| validator.link(selectors.next().flatten()));
+        }
+    }
+
+    /// Removes the documents with the given keys, together with the links to them.
+    fn remove_documents(&mut self, documents: &collections::HashSet<String>) {
+        self.schemes.retain(|key, _| !documents.contains(key));
+        for document in self.schemes.values_mut() {
+            document.visit_references_mut(&mut |reference| {
+                if reference
+                    .link()
+                    .map(|link| documents.contains(&link.document))
+                    .unwrap_or(false)
+                {
+                    reference.unlink();
+                }
+            });
+        }
+        self.link_validators();
     }
+
+    pub(crate) fn get_linked(&self, link: &schema::Link) -> Option<&schema::Schema> {
+        self.document(&link.document)
+            .and_then(|document| document.get_linked(&link.path))
+    }
+}
+
+/// Validates the `default` and `examples` of a schema and of all of its subschemas
+/// against the schema that declares them, see `Scope::validate_defaults`.
+fn check_defaults(scope: &Scope, schema: &schema::Schema) -> Result<(), schema::SchemaError> {
+    if schema.is_schema {
+        if let Some((pointer, state)) = lint::invalid_examples(scope, schema).into_iter().next() {
+            let reasons: Vec<String> = state
+                .errors
+                .iter()
+                .map(|error| format!("`{}` {}", error.get_path(), error.get_title()))
+                .collect();
+            return Err(schema::SchemaError::Malformed {
+                path: format!("{}{}", schema.pointer, pointer),
+                detail: format!(
+                    "The value does not match the schema {}: {}.",
+                    schema.location,
+                    reasons.join(", ")
+                ),
+            });
+        }
+    }
+    for subschema in schema.tree.values() {
+        check_defaults(scope, subschema)?;
+    }
+    Ok(())
+}
+
+fn parse_source(source: &str, url: Option<&url::Url>) -> Result<Value, schema::SchemaError> {
+    serde_json::from_str(source).map_err(|err| schema::SchemaError::Located {
+        location: Box::new(schema::SourceLocation {
+            url: url.cloned(),
+            pointer: String::new(),
+            line: err.line(),
+            column: err.column(),
+        }),
+        error: Box::new(schema::SchemaError::Malformed {
+            path: String::new(),
+            detail: format!("The document is not valid JSON: {err}"),
+        }),
+    })
+}
+
+/// Adds the location of the value the error is about within `source`, or of the closest
+/// enclosing value that can be found.
+fn locate_error(
+    error: schema::SchemaError,
+    source: &str,
+    url: Option<&url::Url>,
+) -> schema::SchemaError {
+    // an unknown key or a malformed keyword comes with the pointer to the keyword
+    let (error, keyword) = match error {
+        schema::SchemaError::Located { location, error } => (*error, Some(location.pointer)),
+        error => (error, None),
+    };
+    let (mut pointer, is_key) = match (&error, keyword) {
+        (schema::SchemaError::UnknownKey(_), Some(keyword)) => (keyword, true),
+        (_, Some(keyword)) => (keyword, false),
+        (_, None) => match error {
+            schema::SchemaError::Malformed { ref path, .. } => (path.clone(), false),
+            schema::SchemaError::InvalidSchema { ref violations, .. } => (
+                violations
+                    .first()
+                    .map(|violation| violation.pointer.clone())
+                    .unwrap_or_default(),
+                false,
+            ),
+            _ => (String::new(), false),
+        },
+    };
+
+    let locations = report::Locator::new(source, true).locate();
+    let mut span = None;
+    loop {
+        if let Some(location) = locations.get(&pointer) {
+            span = if is_key && !pointer.is_empty() {
+                location.key
+            } else {
+                Some(location.value)
+            };
+            break;
+        }
+        match pointer.rfind('/') {
+            Some(idx) => pointer.truncate(idx),
+            None => break,
+        }
+    }
+
+    let position = report::LineIndex::new(source).position(span.map(|span| span.0).unwrap_or(0));
+    schema::SchemaError::Located {
+        location: Box::new(schema::SourceLocation {
+            url: url.cloned(),
+            pointer,
+            line: position.line,
+            column: position.column,
+        }),
+        error: Box::new(error),
+    }
+}
+
+/// Collects the paths of the `.json` files below `root.join(dir)`, relative to `root`.
+/// Symbolic links to directories are not followed.
+fn list_json_files(
+    root: &path::Path,
+    dir: &path::Path,
+    files: &mut Vec<path::PathBuf>,
+) -> Result<(), schema::SchemaError> {
+    let error = |err: std::io::Error| schema::SchemaError::File {
+        path: root.join(dir),
+        detail: format!("cannot read directory: {err}"),
+    };
+    for entry in fs::read_dir(root.join(dir)).map_err(error)? {
+        let entry = entry.map_err(error)?;
+        let relative = dir.join(entry.file_name());
+        if entry.file_type().map_err(error)?.is_dir() {
+            list_json_files(root, &relative, files)?;
+        } else if relative
+            .extension()
+            .map(|ext| ext == "json")
+            .unwrap_or(false)
+            && root.join(&relative).is_file()
+        {
+            files.push(relative);
+        }
+    }
+    Ok(())
 }
 
 #[test]
@@ -309,3 +1013,1003 @@ fn lookup() {
         )
         .is_some());
 }
+
+#[test]
+fn vocabularies_of_metaschema() {
+    let mut scope = Scope::new().set_version(SchemaVersion::Draft2020_12);
+    scope
+        .compile(
+            json!({
+                "$id": "http://example.com/meta/no-validation",
+                "$vocabulary": {
+                    "https://json-schema.org/draft/2020-12/vocab/core": true,
+                    "https://json-schema.org/draft/2020-12/vocab/applicator": true
+                }
+            }),
+            false,
+        )
+        .unwrap();
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "$schema": "http://example.com/meta/no-validation",
+                "properties": { "a": { "minimum": 10 } },
+                "minimum": 10
+            }),
+            false,
+        )
+        .unwrap();
+    assert!(schema.validate(&json!({"a": 1})).is_valid());
+    assert!(schema.validate(&json!(1)).is_valid());
+
+    scope
+        .compile(
+            json!({
+                "$id": "http://example.com/meta/unknown",
+                "$vocabulary": { "http://example.com/vocab/unknown": true }
+            }),
+            false,
+        )
+        .unwrap();
+    assert!(scope
+        .compile(
+            json!({ "$schema": "http://example.com/meta/unknown" }),
+            false,
+        )
+        .is_err());
+}
+
+#[test]
+fn detect_version() {
+    let mut scope = Scope::new().set_version(SchemaVersion::Draft2019_09);
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "$schema": "http://json-schema.org/draft-04/schema#",
+                "maximum": 10,
+                "exclusiveMaximum": true
+            }),
+            false,
+        )
+        .unwrap();
+    assert!(!schema.validate(&json!(10)).is_valid());
+
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "$defs": {
+                    "embedded": {
+                        "$schema": "https://json-schema.org/draft/2020-12/schema",
+                        "$id": "http://example.com/embedded",
+                        "prefixItems": [{ "type": "string" }]
+                    }
+                },
+                "properties": {
+                    "a": { "$ref": "http://example.com/embedded" },
+                    "b": { "prefixItems": [{ "type": "string" }] }
+                }
+            }),
+            false,
+        )
+        .unwrap();
+    assert!(!schema.validate(&json!({"a": [1]})).is_valid());
+    assert!(schema.validate(&json!({"b": [1]})).is_valid());
+}
+
+#[test]
+fn fallback_version() {
+    let mut scope = Scope::new()
+        .set_version(SchemaVersion::Draft2020_12)
+        .set_fallback_version(SchemaVersion::Draft4);
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "$schema": "http://example.com/unknown",
+                "minimum": 10,
+                "exclusiveMinimum": true
+            }),
+            false,
+        )
+        .unwrap();
+    assert!(!schema.validate(&json!(10)).is_valid());
+
+    // schemas without "$schema" keep using the scope version
+    let schema = scope
+        .compile_and_return(json!({ "prefixItems": [{ "type": "string" }] }), false)
+        .unwrap();
+    assert!(!schema.validate(&json!([1])).is_valid());
+}
+
+#[test]
+fn remote_references() {
+    let remote = url::Url::parse("http://localhost:1234/subSchemas.json").unwrap();
+    let folder = url::Url::parse("http://localhost:1234/baseUriChange/folderInteger.json").unwrap();
+    let resolver = resolver::MemoryResolver::new()
+        .with(
+            &remote,
+            json!({
+                "integer": { "type": "integer" },
+                "refToInteger": { "$ref": "#/integer" }
+            }),
+        )
+        .with(&folder, json!({ "type": "integer" }));
+
+    let mut scope = Scope::new();
+    scope.add_resolver(resolver);
+
+    let schema = scope
+        .compile_and_return(
+            json!({ "$ref": "http://localhost:1234/subSchemas.json#/refToInteger" }),
+            false,
+        )
+        .unwrap();
+    assert!(schema.validate(&json!(1)).is_valid());
+    assert!(!schema.validate(&json!("a")).is_valid());
+
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "$id": "http://localhost:1234/",
+                "items": {
+                    "$id": "baseUriChange/",
+                    "items": { "$ref": "folderInteger.json" }
+                }
+            }),
+            false,
+        )
+        .unwrap();
+    assert!(schema.validate(&json!([[1]])).is_valid());
+    assert!(!schema.validate(&json!([["a"]])).is_valid());
+
+    // the loaded documents are part of the scope now
+    assert!(scope.resolve(&remote).is_some());
+    assert!(scope.resolve(&folder).is_some());
+}
+
+#[test]
+fn remote_metaschema() {
+    let resolver = resolver::MemoryResolver::new().with(
+        &url::Url::parse("http://localhost:1234/metaschema-no-validation.json").unwrap(),
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": "http://localhost:1234/metaschema-no-validation.json",
+            "$vocabulary": {
+                "https://json-schema.org/draft/2020-12/vocab/applicator": true,
+                "https://json-schema.org/draft/2020-12/vocab/core": true
+            }
+        }),
+    );
+
+    let mut scope = Scope::new();
+    scope.add_resolver(resolver);
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "$schema": "http://localhost:1234/metaschema-no-validation.json",
+                "properties": {
+                    "badProperty": false,
+                    "numberProperty": { "minimum": 10 }
+                }
+            }),
+            false,
+        )
+        .unwrap();
+    assert!(schema.validate(&json!({ "numberProperty": 1 })).is_valid());
+    assert!(!schema.validate(&json!({ "badProperty": 1 })).is_valid());
+}
+
+#[test]
+fn remote_defaults() {
+    let resolver = resolver::MemoryResolver::new().with(
+        &url::Url::parse("http://localhost:1234/name.json").unwrap(),
+        json!({ "type": "string", "default": "anonymous" }),
+    );
+
+    let mut scope = Scope::new().supply_defaults();
+    scope.add_resolver(resolver);
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "properties": { "name": { "$ref": "http://localhost:1234/name.json" } }
+            }),
+            false,
+        )
+        .unwrap();
+    let state = schema.validate(&json!({}));
+    assert!(state.is_valid());
+    assert_eq!(state.replacement, Some(json!({ "name": "anonymous" })));
+}
+
+#[test]
+fn validate_defaults() {
+    let resolver = resolver::MemoryResolver::new().with(
+        &url::Url::parse("http://localhost:1234/name.json").unwrap(),
+        json!({ "type": "string", "default": 42 }),
+    );
+    let mut scope = Scope::new().supply_defaults().validate_defaults();
+    scope.add_resolver(resolver);
+
+    let schema = json!({
+        "properties": {
+            "size": {
+                "type": "integer",
+                "default": 1,
+                "examples": [2, "large"]
+            }
+        }
+    });
+    match scope.compile(schema, false) {
+        Err(schema::SchemaError::Malformed { path, .. }) => {
+            assert_eq!(path, "/properties/size/examples/1")
+        }
+        result => panic!("unexpected {:?}", result),
+    }
+
+    // referenced documents are checked as well, and none of them is kept on failure
+    let schema = json!({
+        "properties": { "name": { "$ref": "http://localhost:1234/name.json" } }
+    });
+    match scope.compile(schema, false) {
+        Err(schema::SchemaError::Malformed { path, .. }) => assert_eq!(path, "/default"),
+        result => panic!("unexpected {:?}", result),
+    }
+    assert!(scope.schemes.is_empty());
+
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "properties": {
+                    "size": { "type": "integer", "default": 1, "examples": [2, 3] },
+                    "tags": { "items": { "$ref": "#/definitions/tag" }, "default": ["a"] }
+                },
+                "definitions": { "tag": { "type": "string" } }
+            }),
+            false,
+        )
+        .unwrap();
+    let state = schema.validate(&json!({}));
+    assert_eq!(state.replacement, Some(json!({ "size": 1, "tags": ["a"] })));
+}
+
+#[test]
+fn directory_resolver() {
+    let dir = std::env::temp_dir().join(format!(
+        "valico-{}",
+        helpers::generate_id().host_str().unwrap()
+    ));
+    std::fs::create_dir_all(dir.join("nested")).unwrap();
+    std::fs::write(dir.join("nested/string.json"), r#"{ "type": "string" }"#).unwrap();
+
+    let resolver = resolver::DirectoryResolver::new().map("http://localhost:1234/", &dir);
+    let mut scope = Scope::new();
+    scope.add_resolver(resolver);
+    let result = scope.compile_and_return(
+        json!({ "$ref": "http://localhost:1234/nested/string.json" }),
+        false,
+    );
+    let valid = result.map(|schema| {
+        (
+            schema.validate(&json!("a")).is_valid(),
+            schema.validate(&json!(1)).is_valid(),
+        )
+    });
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(valid.unwrap(), (true, false));
+}
+
+#[test]
+fn directory_resolver_stays_in_directory() {
+    use super::resolver::SchemaResolver;
+
+    let dir = std::env::temp_dir().join(format!(
+        "valico-{}",
+        helpers::generate_id().host_str().unwrap()
+    ));
+    std::fs::create_dir_all(dir.join("schemas")).unwrap();
+    std::fs::create_dir_all(dir.join("schemas-private")).unwrap();
+    std::fs::write(dir.join("schemas/string.json"), r#"{ "type": "string" }"#).unwrap();
+    std::fs::write(dir.join("schemas-private/secret.json"), "{}").unwrap();
+    std::fs::write(dir.join("secret.json"), "{}").unwrap();
+
+    let resolver = resolver::DirectoryResolver::new()
+        .map("http://localhost:1234/schemas", dir.join("schemas"));
+    let resolve = |url: &str| {
+        resolver
+            .resolve(&url::Url::parse(url).unwrap())
+            .unwrap()
+            .is_some()
+    };
+    let found = vec![
+        resolve("http://localhost:1234/schemas/string.json"),
+        resolve("http://localhost:1234/schemas/string.json?version=2"),
+        resolve("http://localhost:1234/schemas-private/secret.json"),
+        resolve("http://localhost:1234/schemas/..%2Fsecret.json"),
+        resolve("http://localhost:1234/schemas/%2E%2E/secret.json"),
+        resolve(&format!(
+            "http://localhost:1234/schemas/{}",
+            dir.join("secret.json")
+                .display()
+                .to_string()
+                .replace('/', "%2F")
+        )),
+        resolve("http://localhost:4321/schemas/string.json"),
+    ];
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(found, vec![true, true, false, false, false, false, false]);
+}
+
+#[test]
+fn failed_compilation_is_rolled_back() {
+    let id = url::Url::parse("http://localhost:1234/root.json").unwrap();
+    let valid = url::Url::parse("http://localhost:1234/valid.json").unwrap();
+    let resolver = resolver::MemoryResolver::new()
+        .with(&valid, json!({ "type": "string" }))
+        .with(
+            &url::Url::parse("http://localhost:1234/invalid.json").unwrap(),
+            json!({ "type": 10 }),
+        );
+    let mut scope = Scope::new();
+    scope.add_resolver(resolver);
+
+    let result = scope.compile_with_id(
+        &id,
+        json!({
+            "properties": {
+                "a": { "$ref": "valid.json" },
+                "b": { "$ref": "invalid.json" }
+            }
+        }),
+        false,
+    );
+    assert!(result.is_err());
+    assert!(scope.resolve(&id).is_none());
+    assert!(scope.resolve(&valid).is_none());
+
+    // the id can be compiled again
+    scope
+        .compile_with_id(&id, json!({ "$ref": "valid.json" }), false)
+        .unwrap();
+    assert!(scope.resolve(&valid).is_some());
+}
+
+#[test]
+fn load_dir() {
+    let dir = std::env::temp_dir().join(format!(
+        "valico-{}",
+        helpers::generate_id().host_str().unwrap()
+    ));
+    std::fs::create_dir_all(dir.join("common")).unwrap();
+    std::fs::create_dir_all(dir.join("types")).unwrap();
+    std::fs::write(
+        dir.join("person.json"),
+        r#"{
+            "properties": {
+                "address": { "$ref": "common/address.json" },
+                "zip": { "$ref": "common/address.json#/definitions/zip" },
+                "country": { "$ref": "types/country.json" }
+            }
+        }"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("common/address.json"),
+        r##"{
+            "definitions": { "zip": { "type": "string", "pattern": "^[0-9]{5}$" } },
+            "properties": {
+                "zip": { "$ref": "#/definitions/zip" },
+                "resident": { "$ref": "../person.json" }
+            }
+        }"##,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("types/country.json"),
+        r#"{ "type": "string", "default": "DE" }"#,
+    )
+    .unwrap();
+    std::fs::write(dir.join("types/notes.txt"), "not a schema").unwrap();
+
+    let base = url::Url::parse("http://example.com/schemas").unwrap();
+    let mut scope = Scope::new().supply_defaults();
+    let ids = scope.load_dir(&dir, &base, false);
+    let mut conflicting = Scope::new();
+    conflicting
+        .compile_with_id(
+            &url::Url::parse("http://example.com/schemas/person.json").unwrap(),
+            json!({}),
+            false,
+        )
+        .unwrap();
+    let conflict = conflicting.load_dir(&dir, &base, false);
+    let missing = Scope::new().load_dir(dir.join("missing"), &base, false);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let ids: Vec<String> = ids.unwrap().iter().map(|id| id.to_string()).collect();
+    assert_eq!(
+        ids,
+        vec![
+            "http://example.com/schemas/common/address.json",
+            "http://example.com/schemas/person.json",
+            "http://example.com/schemas/types/country.json",
+        ]
+    );
+    assert!(scope.check_references().is_empty());
+    let person = scope.resolve(&url::Url::parse(&ids[1]).unwrap()).unwrap();
+    let state = person.validate(&json!({ "zip": "12345", "address": { "zip": "123" } }));
+    assert_eq!(state.errors.len(), 1);
+    assert_eq!(state.errors[0].get_path(), "/address/zip");
+    let state = person.validate(&json!({ "address": { "resident": { "zip": 1 } } }));
+    assert_eq!(state.errors[0].get_path(), "/address/resident/zip");
+    assert_eq!(
+        person.validate(&json!({})).replacement,
+        Some(json!({ "country": "DE" }))
+    );
+
+    assert!(matches!(conflict, Err(schema::SchemaError::IdConflicts)));
+    assert!(matches!(missing, Err(schema::SchemaError::File { .. })));
+}
+
+#[test]
+fn load_dir_is_retried() {
+    let dir = std::env::temp_dir().join(format!(
+        "valico-{}",
+        helpers::generate_id().host_str().unwrap()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.json"), r#"{ "type": "string" }"#).unwrap();
+    std::fs::write(dir.join("b.json"), r#"{ "type": 1 }"#).unwrap();
+
+    let base_uri = url::Url::parse("http://example.com/schemas/").unwrap();
+    let mut scope = Scope::new();
+    let failed = scope.load_dir(&dir, &base_uri, false);
+    let unknown = scope.resolve(&base_uri.join("a.json").unwrap()).is_none();
+
+    std::fs::write(dir.join("b.json"), r#"{ "type": "integer" }"#).unwrap();
+    let retried = scope.load_dir(&dir, &base_uri, false);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    match failed {
+        Err(schema::SchemaError::Located { location, .. }) => {
+            assert_eq!(location.pointer, "/type");
+            assert_eq!((location.line, location.column), (1, 11));
+        }
+        result => panic!("unexpected {:?}", result),
+    }
+    assert!(unknown);
+    assert_eq!(retried.unwrap().len(), 2);
+}
+
+#[test]
+fn load_dir_bans_unknown_keywords() {
+    let dir = std::env::temp_dir().join(format!(
+        "valico-{}",
+        helpers::generate_id().host_str().unwrap()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.json"), r#"{ "$ref": "b.json" }"#).unwrap();
+    std::fs::write(dir.join("b.json"), r#"{ "maxLenght": 3 }"#).unwrap();
+
+    let base_uri = url::Url::parse("http://example.com/schemas/").unwrap();
+    let banned = Scope::new().load_dir(&dir, &base_uri, true);
+    let allowed = Scope::new().load_dir(&dir, &base_uri, false);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    match banned.map_err(schema::SchemaError::unlocated) {
+        Err(schema::SchemaError::UnknownKey(key)) => assert_eq!(key, "maxLenght"),
+        result => panic!("unexpected {:?}", result),
+    }
+    assert_eq!(allowed.unwrap().len(), 2);
+}
+
+#[test]
+fn check_references() {
+    let mut scope = Scope::new();
+    let id = url::Url::parse("http://example.com/schema").unwrap();
+    scope
+        .compile_with_id(
+            &id,
+            json!({
+                "definitions": { "name": { "type": "string" } },
+                "properties": {
+                    "name": { "$ref": "#/definitions/name" },
+                    "age": { "$ref": "#/definitions/age" },
+                    "address": { "allOf": [{ "$ref": "http://example.com/address" }] }
+                }
+            }),
+            false,
+        )
+        .unwrap();
+
+    let dangling = scope.check_references();
+    assert_eq!(
+        dangling,
+        vec![
+            DanglingReference {
+                document: id.clone(),
+                schema_path: "/properties/address/allOf/0".to_string(),
+                reference: url::Url::parse("http://example.com/address").unwrap(),
+            },
+            DanglingReference {
+                document: id.clone(),
+                schema_path: "/properties/age".to_string(),
+                reference: url::Url::parse("http://example.com/schema#/definitions/age").unwrap(),
+            },
+        ]
+    );
+
+    scope
+        .compile_with_id(
+            &url::Url::parse("http://example.com/address").unwrap(),
+            json!({ "type": "object" }),
+            false,
+        )
+        .unwrap();
+    assert_eq!(scope.check_references().len(), 1);
+}
+
+#[test]
+fn validate_against_metaschema() {
+    let mut scope = Scope::new().validate_against_metaschema();
+
+    match scope.compile(json!({ "minimum": "5", "required": "name" }), false) {
+        Err(schema::SchemaError::InvalidSchema {
+            metaschema,
+            violations,
+        }) => {
+            assert_eq!(metaschema, SchemaVersion::Draft7.metaschema_url());
+            let mut pointers: Vec<&str> = violations.iter().map(|v| &v.pointer[..]).collect();
+            pointers.sort_unstable();
+            assert_eq!(pointers, vec!["/minimum", "/required"]);
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    match scope.compile(
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "properties": { "name": { "type": "strin" } }
+        }),
+        false,
+    ) {
+        Err(schema::SchemaError::InvalidSchema {
+            metaschema,
+            violations,
+        }) => {
+            assert_eq!(metaschema, SchemaVersion::Draft2020_12.metaschema_url());
+            assert!(violations
+                .iter()
+                .any(|v| v.pointer == "/properties/name/type"));
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    for version in [
+        SchemaVersion::Draft4,
+        SchemaVersion::Draft6,
+        SchemaVersion::Draft7,
+        SchemaVersion::Draft2019_09,
+        SchemaVersion::Draft2020_12,
+    ] {
+        let mut scope = Scope::new()
+            .set_version(version)
+            .validate_against_metaschema();
+        assert!(scope
+            .compile(
+                json!({ "properties": { "name": { "type": "string" } } }),
+                false
+            )
+            .is_ok());
+        assert!(scope.compile(json!({ "maxLength": -1 }), false).is_err());
+    }
+}
+
+#[test]
+fn reference_to_metaschema() {
+    let mut scope = Scope::new();
+    let schema = scope
+        .compile_and_return(
+            json!({ "$ref": "http://json-schema.org/draft-07/schema#" }),
+            false,
+        )
+        .unwrap();
+    assert!(schema.validate(&json!({ "minimum": 5 })).is_valid());
+    assert!(!schema
+        .validate(&json!({ "definitions": { "foo": { "type": 1 } } }))
+        .is_valid());
+
+    // the keywords next to "$ref" apply according to the draft of the metaschema
+    let schema = scope
+        .compile_and_return(
+            json!({ "$ref": "https://json-schema.org/draft/2020-12/schema" }),
+            false,
+        )
+        .unwrap();
+    assert!(schema.validate(&json!({ "$id": "http://a/b" })).is_valid());
+    assert!(!schema
+        .validate(&json!({ "$id": "http://a/b#frag" }))
+        .is_valid());
+
+    // the metaschemas are shared, not compiled into the scope
+    assert_eq!(scope.schemes.len(), 2);
+}
+
+#[test]
+fn collect_annotations() {
+    let mut scope = Scope::new()
+        .set_version(SchemaVersion::Draft2019_09)
+        .collect_annotations();
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "title": "Pet",
+                "properties": {
+                    "name": { "$ref": "#/$defs/name", "description": "The name" },
+                    "kind": {
+                        "anyOf": [
+                            { "const": "cat", "title": "Cat" },
+                            { "const": "dog", "title": "Dog" },
+                            { "type": "string", "deprecated": true }
+                        ]
+                    }
+                },
+                "$defs": {
+                    "name": { "type": "string", "title": "Name", "examples": ["Rex"] }
+                }
+            }),
+            false,
+        )
+        .unwrap();
+
+    let state = schema.validate(&json!({ "name": "Tom", "kind": "cat" }));
+    assert!(state.is_valid());
+    let annotations: Vec<(&str, &str, &Value)> = state
+        .annotations()
+        .iter()
+        .flat_map(|(path, annotations)| {
+            annotations
+                .iter()
+                .map(move |a| (path.as_str(), a.keyword_location.as_str(), &a.value))
+        })
+        .collect();
+    assert_eq!(
+        annotations,
+        vec![
+            ("", "/title", &json!("Pet")),
+            ("/kind", "/properties/kind/anyOf/0/title", &json!("Cat")),
+            ("/kind", "/properties/kind/anyOf/2/deprecated", &json!(true)),
+            ("/name", "/properties/name/description", &json!("The name")),
+            ("/name", "/properties/name/$ref/title", &json!("Name")),
+            ("/name", "/properties/name/$ref/examples", &json!(["Rex"])),
+        ]
+    );
+
+    // the annotations of failed schemas are dropped
+    let state = schema.validate(&json!({ "name": 1, "kind": "cat" }));
+    assert!(!state.is_valid());
+    assert!(state.annotations().is_empty());
+
+    // and nothing is collected unless asked for
+    let mut scope = Scope::new();
+    let schema = scope
+        .compile_and_return(json!({ "title": "Pet" }), false)
+        .unwrap();
+    assert!(schema.validate(&json!({})).annotations().is_empty());
+}
+
+#[test]
+fn collect_annotations_of_declared_vocabularies() {
+    let mut scope = Scope::new()
+        .set_version(SchemaVersion::Draft2020_12)
+        .collect_annotations();
+    scope
+        .compile(
+            json!({
+                "$id": "http://example.com/meta/no-meta-data",
+                "$vocabulary": {
+                    "https://json-schema.org/draft/2020-12/vocab/core": true,
+                    "https://json-schema.org/draft/2020-12/vocab/format-annotation": true
+                }
+            }),
+            false,
+        )
+        .unwrap();
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "$schema": "http://example.com/meta/no-meta-data",
+                "title": "Date",
+                "format": "date"
+            }),
+            false,
+        )
+        .unwrap();
+
+    let state = schema.validate(&json!("2020-01-01"));
+    let keywords: Vec<&str> = state.annotations()[""]
+        .iter()
+        .map(|annotation| annotation.keyword.as_str())
+        .collect();
+    assert_eq!(keywords, vec!["format"]);
+}
+
+#[test]
+fn validate_concurrently() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Scope>();
+    assert_send_sync::<schema::Schema>();
+
+    let mut scope = Scope::new().supply_defaults();
+    let id = scope
+        .compile(
+            json!({
+                "type": "object",
+                "properties": {
+                    "id": { "type": "integer", "minimum": 0 },
+                    "name": { "type": "string", "default": "anonymous" }
+                },
+                "required": ["id"]
+            }),
+            false,
+        )
+        .unwrap();
+    let scope = std::sync::Arc::new(scope);
+
+    let threads: Vec<_> = (0..8)
+        .map(|thread| {
+            let scope = scope.clone();
+            let id = id.clone();
+            std::thread::spawn(move || {
+                let schema = scope.resolve(&id).unwrap();
+                for idx in 0..100 {
+                    let state = schema.validate(&json!({ "id": thread * 100 + idx }));
+                    assert!(state.is_valid());
+                    assert_eq!(
+                        state.replacement,
+                        Some(json!({ "id": thread * 100 + idx, "name": "anonymous" }))
+                    );
+                    assert!(!schema.validate(&json!({ "id": -1 })).is_valid());
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+#[test]
+fn link_references() {
+    fn assert_linked(scope: &Scope, linked: bool) {
+        for document in scope.schemes.values() {
+            document.visit_references(&mut |reference| {
+                assert_eq!(reference.is_linked(), linked, "{}", reference.url);
+                if let Some(ref link) = reference.link {
+                    let target = scope.find(&reference.url).unwrap().1;
+                    assert!(std::ptr::eq(scope.get_linked(link).unwrap(), target));
+                }
+            });
+        }
+    }
+
+    let mut scope = Scope::new().set_version(SchemaVersion::Draft2019_09);
+    scope
+        .compile(
+            json!({
+                "$id": "http://example.com/tree",
+                "type": "object",
+                "properties": {
+                    "children": { "type": "array", "items": { "$ref": "#" } },
+                    "value": { "$ref": "#/$defs/a%20value" },
+                    "label": { "$ref": "#label" },
+                    "node": { "$ref": "node#/properties/id" }
+                },
+                "$defs": {
+                    "a value": { "anyOf": [{ "type": "number" }, { "$ref": "#/$defs/other" }] },
+                    "other": { "$anchor": "label", "type": "string" },
+                    "node": {
+                        "$id": "node",
+                        "properties": { "id": { "type": "integer" } }
+                    }
+                }
+            }),
+            false,
+        )
+        .unwrap();
+    assert_linked(&scope, true);
+
+    // references to documents that are not known yet are linked once they are added
+    let id = scope
+        .compile(
+            json!({
+                "$id": "http://example.com/forest",
+                "items": { "$ref": "http://example.com/leaf" }
+            }),
+            false,
+        )
+        .unwrap();
+    assert!(!scope
+        .resolve(&id)
+        .unwrap()
+        .validate(&json!([1]))
+        .is_strictly_valid());
+    scope
+        .compile(
+            json!({ "$id": "http://example.com/leaf", "type": "string" }),
+            false,
+        )
+        .unwrap();
+    assert_linked(&scope, true);
+
+    let schema = scope.resolve(&id).unwrap();
+    assert!(schema.validate(&json!(["a"])).is_strictly_valid());
+    assert!(!schema.validate(&json!([1])).is_valid());
+    let tree = scope
+        .resolve(&url::Url::parse("http://example.com/tree").unwrap())
+        .unwrap();
+    assert!(tree
+        .validate(&json!({ "children": [{ "value": 1, "node": 2, "label": "x" }] }))
+        .is_strictly_valid());
+    assert!(!tree
+        .validate(&json!({ "children": [{ "children": [{ "node": "x" }] }] }))
+        .is_valid());
+}
+
+#[test]
+fn error_limit() {
+    let mut scope = Scope::new();
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": "object",
+                "properties": {
+                    "list": {
+                        "items": { "type": "integer" },
+                        "contains": { "const": 1 },
+                        "maxContains": 1
+                    },
+                    "name": { "type": "string", "minLength": 3 }
+                },
+                "allOf": [{ "required": ["list"] }, { "required": ["name"] }],
+                "oneOf": [{ "minProperties": 2 }, { "required": ["extra"] }]
+            }),
+            false,
+        )
+        .unwrap();
+
+    let data = json!({ "list": ["a", "b", "c"], "name": 1, "extra": true });
+    assert_eq!(schema.validate(&data).errors.len(), 6);
+    for limit in 1..6 {
+        let state = schema.validate_with_limit(&data, limit);
+        assert_eq!(state.errors.len(), limit);
+    }
+    assert_eq!(schema.validate_with_limit(&data, 10).errors.len(), 6);
+    assert!(!schema.is_valid(&data));
+    assert!(!schema.is_valid(&json!({})));
+    assert!(!schema.is_valid(&json!({ "list": [1, 2], "name": "ab" })));
+    assert!(!schema.is_valid(&json!({ "list": [1, 1], "name": "abc" })));
+    assert!(schema.is_valid(&json!({ "list": [1, 2], "name": "abc" })));
+}
+
+#[test]
+fn recursion_limits() {
+    let codes = |state: super::ValidationState| -> Vec<(String, String)> {
+        state
+            .errors
+            .iter()
+            .map(|error| (error.get_code().to_string(), error.get_path().to_string()))
+            .collect()
+    };
+    let cycle = |path: &str| vec![("reference_cycle".to_string(), path.to_string())];
+
+    let mut scope = Scope::new();
+    let schema = scope
+        .compile_and_return(json!({ "$ref": "#" }), false)
+        .unwrap();
+    assert_eq!(codes(schema.validate(&json!(1))), cycle(""));
+
+    // a cycle fails the whole validation, even where a failure would be ignored otherwise
+    let schema = scope
+        .compile_and_return(
+            json!({
+                "definitions": {
+                    "a": { "allOf": [{ "$ref": "#/definitions/b" }] },
+                    "b": { "anyOf": [{ "type": "string" }, { "$ref": "#/definitions/a" }] }
+                },
+                "properties": {
+                    "x": { "not": { "$ref": "#/definitions/a" } }
+                }
+            }),
+            false,
+        )
+        .unwrap();
+    assert!(schema.validate(&json!({ "y": 1 })).is_strictly_valid());
+    let state = schema.validate(&json!({ "y": 1, "x": 1 }));
+    assert_eq!(
+        state.errors[0].get_keyword_location(),
+        Some("/properties/x/not/$ref/allOf/0/$ref/anyOf/1/$ref")
+    );
+    assert_eq!(codes(state), cycle("/x"));
+    let output = schema.validate_with_output(&json!({ "x": 1 }), super::OutputFormat::Basic);
+    assert_eq!(output.errors.len(), 1);
+    assert_eq!(output.errors[0].instance_location.as_deref(), Some("/x"));
+
+    // recursion that consumes the instance is bounded by its depth
+    let mut scope = Scope::new().max_depth(5);
+    let schema = scope
+        .compile_and_return(json!({ "items": { "$ref": "#" } }), false)
+        .unwrap();
+    assert!(schema.validate(&json!([[[[[1]]]]])).is_strictly_valid());
+    assert_eq!(
+        codes(schema.validate(&json!([[[[[[1]]]]]]))),
+        vec![("max_depth".to_string(), "/0/0/0/0/0/0".to_string())]
+    );
+    assert_eq!(
+        codes(schema.validate_in(&json!([[[[[1]]]]]), "/deep")),
+        vec![]
+    );
+
+    let mut scope = Scope::new().max_steps(50);
+    let schema = scope
+        .compile_and_return(json!({ "items": { "type": "integer" } }), false)
+        .unwrap();
+    assert!(schema.validate(&json!(vec![1; 49])).is_strictly_valid());
+    assert_eq!(
+        codes(schema.validate(&json!(vec![1; 50]))),
+        vec![("max_steps".to_string(), "/49".to_string())]
+    );
+}
+
+#[test]
+fn compile_source() {
+    let source = r#"{
+  "properties": {
+    "name": { "type": "string", "maxLenght": 3 },
+    "size": { "minimum": "small" }
+  }
+}"#;
+    let url = url::Url::parse("file:///schemas/item.json").unwrap();
+
+    let mut scope = Scope::new();
+    let error = scope
+        .compile_source_with_id(&url, source, true)
+        .unwrap_err();
+    match error {
+        schema::SchemaError::Located {
+            ref location,
+            ref error,
+        } => {
+            assert_eq!(location.pointer, "/properties/name/maxLenght");
+            assert_eq!((location.line, location.column), (3, 33));
+            match **error {
+                schema::SchemaError::UnknownKey(ref key) => assert_eq!(key, "maxLenght"),
+                ref error => panic!("unexpected {:?}", error),
+            }
+        }
+        ref error => panic!("unexpected {:?}", error),
+    }
+    assert_eq!(
+        error.to_string(),
+        "/schemas/item.json:3:33 (`/properties/name/maxLenght`): unknown key: maxLenght"
+    );
+
+    match scope.compile_source(source, false) {
+        Err(schema::SchemaError::Located { location, .. }) => {
+            assert_eq!(location.pointer, "/properties/size/minimum");
+            assert_eq!((location.line, location.column), (4, 26));
+        }
+        result => panic!("unexpected {:?}", result),
+    }
+
+    match scope.compile_source("{\n  \"type\": \"string\",\n}", false) {
+        Err(schema::SchemaError::Located { location, .. }) => {
+            assert_eq!((location.line, location.column), (3, 1));
+        }
+        result => panic!("unexpected {:?}", result),
+    }
+
+    assert!(scope
+        .compile_source("{ \"type\": \"string\" }", true)
+        .is_ok());
+}
@@ -1,51 +1,65 @@
 use serde_json::Value;
-use url;
 
-use super::super::scope;
+use super::super::schema;
 
 #[allow(missing_copy_implementations)]
 pub struct Conditional {
-    pub if_: url::Url,
-    pub then_: Option<url::Url>,
-    pub else_: Option<url::Url>,
+    pub if_: schema::SchemaRef,
+    pub then_: Option<schema::SchemaRef>,
+    pub else_: Option<schema::SchemaRef>,
 }
 
 impl super::Validator for Conditional {
+    fn references(&self) -> Vec<&schema::SchemaRef> {
+        let mut urls = vec![&self.if_];
+        urls.extend(self.then_.iter());
+        urls.extend(self.else_.iter());
+        urls
+    }
+
+    fn references_mut(&mut self) -> Vec<&mut schema::SchemaRef> {
+        let mut urls = vec![&mut self.if_];
+        urls.extend(self.then_.iter_mut());
+        urls.extend(self.else_.iter_mut());
+        urls
+    }
+
     fn validate(
         &self,
         val: &Value,
         path: &str,
-        scope: &scope::Scope,
+        ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let mut state = super::ValidationState::new();
 
-        let schema_if_ = scope.resolve(&self.if_);
+        let schema_if_ = ctx.resolve_ref(&self.if_);
         if let Some(schema_if) = schema_if_ {
             // TODO should the validation be strict?
             let if_state = schema_if.validate_in(val, path);
             if if_state.is_valid() {
                 state.evaluated.extend(if_state.evaluated);
-                if self.then_.is_some() {
-                    let schema_then_ = scope.resolve(self.then_.as_ref().unwrap());
+                state.append_annotations(if_state.annotations);
+                if let Some(ref then_) = self.then_ {
+                    let schema_then_ = ctx.resolve_ref(then_);
 
                     if let Some(schema_then) = schema_then_ {
                         state.append(schema_then.validate_in(val, path));
                     } else {
-                        state.missing.push(self.then_.as_ref().unwrap().clone());
+                        state.missing.push(then_.url.clone());
                     }
                 }
-            } else if self.else_.is_some() {
-                let schema_else_ = scope.resolve(self.else_.as_ref().unwrap());
+            } else if let Some(ref else_) = self.else_ {
+                let schema_else_ = ctx.resolve_ref(else_);
 
                 if let Some(schema_else) = schema_else_ {
                     state.append(schema_else.validate_in(val, path));
                 } else {
-                    state.missing.push(self.else_.as_ref().unwrap().clone());
+                    state.missing.push(else_.url.clone());
                 }
             }
         } else {
-            state.missing.push(self.if_.clone());
+            state.missing.push(self.if_.url.clone());
         }
         state
     }
@@ -2,7 +2,7 @@ use serde_json::Value;
 
 use super::super::errors;
 use super::super::helpers::is_matching;
-use super::super::scope;
+use super::super::schema;
 
 #[allow(missing_copy_implementations)]
 pub struct Const {
@@ -14,7 +14,7 @@ impl super::Validator for Const {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let mut state = super::ValidationState::new();
@@ -22,6 +22,8 @@ impl super::Validator for Const {
         if !is_matching(&self.item, val) {
             state.errors.push(Box::new(errors::Const {
                 path: path.to_string(),
+                location: Default::default(),
+                expected: self.item.clone(),
             }))
         } else {
             state.evaluated.insert(path.to_owned());
@@ -1,27 +1,40 @@
 use serde_json::Value;
 use std::borrow::Cow;
+use std::mem;
 
 use super::super::errors;
-use super::super::scope;
+use super::super::schema;
 
 #[allow(missing_copy_implementations)]
 pub struct Contains {
-    pub url: url::Url,
+    pub url: schema::SchemaRef,
     pub max_contains: Option<u64>,
     pub min_contains: Option<u64>,
+    pub max_contains_location: schema::SchemaLocation,
+    pub min_contains_location: schema::SchemaLocation,
+    /// Since draft 2020-12 the items matched by `contains` count as evaluated.
+    pub mark_evaluated: bool,
 }
 
 impl super::Validator for Contains {
+    fn references(&self) -> Vec<&schema::SchemaRef> {
+        vec![&self.url]
+    }
+
+    fn references_mut(&mut self) -> Vec<&mut schema::SchemaRef> {
+        vec![&mut self.url]
+    }
+
     fn validate(
         &self,
         val: &Value,
         path: &str,
-        scope: &scope::Scope,
+        ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let mut array = Cow::Borrowed(nonstrict_process!(val.as_array(), path));
 
-        let schema = scope.resolve(&self.url);
+        let schema = ctx.resolve_ref(&self.url);
         let mut state = super::ValidationState::new();
 
         if let Some(schema) = schema {
@@ -32,10 +45,22 @@ impl super::Validator for Contains {
                 let mut result = schema.validate_in(item, item_path.as_ref());
                 if result.is_valid() {
                     matched_count += 1;
+                    state.append_annotations(mem::take(&mut result.annotations));
                     if let Some(result) = result.replacement.take() {
                         array.to_mut()[idx] = result;
                     }
-                    if self.max_contains.is_none() && self.min_contains.is_none() {
+                    if self.mark_evaluated {
+                        state.evaluated.insert(item_path);
+                    } else if self.max_contains.is_none() && self.min_contains.is_none() {
+                        break;
+                    }
+                    if ctx.has_error_limit()
+                        && self
+                            .max_contains
+                            .map(|max| matched_count > max)
+                            .unwrap_or(false)
+                    {
+                        // maxContains already fails, counting further does not matter
                         break;
                     }
                 }
@@ -44,6 +69,7 @@ impl super::Validator for Contains {
             if matched_count == 0 && self.min_contains != Some(0) {
                 state.errors.push(Box::new(errors::Contains {
                     path: path.to_string(),
+                    location: Default::default(),
                 }))
             }
 
@@ -54,6 +80,7 @@ impl super::Validator for Contains {
             {
                 state.errors.push(Box::new(errors::ContainsMinMax {
                     path: path.to_string(),
+                    location: self.max_contains_location.error_location(),
                 }));
             }
 
@@ -64,10 +91,11 @@ impl super::Validator for Contains {
             {
                 state.errors.push(Box::new(errors::ContainsMinMax {
                     path: path.to_string(),
+                    location: self.min_contains_location.error_location(),
                 }));
             }
         } else {
-            state.missing.push(self.url.clone());
+            state.missing.push(self.url.url.clone());
         }
 
         state.set_replacement(array);
@@ -1,7 +1,7 @@
 use serde_json::Value;
 
 use super::super::errors;
-use super::super::scope;
+use super::super::schema;
 
 use super::super::keywords::content_media::{ContentEncoding, ContentMediaType};
 
@@ -9,6 +9,8 @@ use super::super::keywords::content_media::{ContentEncoding, ContentMediaType};
 pub struct ContentMedia {
     pub type_: Option<ContentMediaType>,
     pub encoding: Option<ContentEncoding>,
+    pub type_location: schema::SchemaLocation,
+    pub encoding_location: schema::SchemaLocation,
 }
 
 impl super::Validator for ContentMedia {
@@ -16,19 +18,16 @@ impl super::Validator for ContentMedia {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
-        let decoded_val = if self.encoding.is_some() && val.is_string() {
-            let v = self
-                .encoding
-                .as_ref()
-                .unwrap()
-                .decode_val(val.as_str().unwrap());
+        let decoded_val = if let (Some(encoding), Some(string)) = (&self.encoding, val.as_str()) {
+            let v = encoding.decode_val(string);
             if v.is_err() {
                 return val_error!(errors::Format {
                     path: path.to_string(),
                     detail: v.err().unwrap(),
+                    location: self.encoding_location.error_location(),
                 });
             }
             Some(Value::String(v.ok().unwrap()))
@@ -36,11 +35,7 @@ impl super::Validator for ContentMedia {
             None
         };
 
-        let val_ = if decoded_val.is_some() {
-            decoded_val.as_ref().unwrap()
-        } else {
-            val
-        };
+        let val_ = decoded_val.as_ref().unwrap_or(val);
 
         if self.type_.is_some()
             && val_.is_string()
@@ -53,6 +48,7 @@ impl super::Validator for ContentMedia {
             return val_error!(errors::Format {
                 path: path.to_string(),
                 detail: "".to_string(),
+                location: self.type_location.error_location(),
             });
         }
 
@@ -2,11 +2,11 @@ use serde_json::Value;
 use std::borrow::Cow;
 
 use super::super::errors;
-use super::super::scope;
+use super::super::schema;
 
 #[derive(Debug)]
 pub enum DepKind {
-    Schema(url::Url),
+    Schema(schema::SchemaRef),
     Property(Vec<String>),
 }
 
@@ -16,11 +16,31 @@ pub struct Dependencies {
 }
 
 impl super::Validator for Dependencies {
+    fn references(&self) -> Vec<&schema::SchemaRef> {
+        self.items
+            .iter()
+            .filter_map(|(_, dep)| match dep {
+                DepKind::Schema(url) => Some(url),
+                DepKind::Property(_) => None,
+            })
+            .collect()
+    }
+
+    fn references_mut(&mut self) -> Vec<&mut schema::SchemaRef> {
+        self.items
+            .iter_mut()
+            .filter_map(|(_, dep)| match dep {
+                DepKind::Schema(url) => Some(url),
+                DepKind::Property(_) => None,
+            })
+            .collect()
+    }
+
     fn validate(
         &self,
         val: &Value,
         path: &str,
-        scope: &scope::Scope,
+        ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let mut state = super::ValidationState::new();
@@ -33,7 +53,7 @@ impl super::Validator for Dependencies {
             if object.get(key).is_some() {
                 match dep {
                     DepKind::Schema(ref url) => {
-                        let schema = scope.resolve(url);
+                        let schema = ctx.resolve_ref(url);
                         if let Some(schema) = schema {
                             let mut result = schema.validate_in(&object, path);
                             if result.is_valid() && result.replacement.is_some() {
@@ -41,7 +61,7 @@ impl super::Validator for Dependencies {
                             }
                             state.append(result);
                         } else {
-                            state.missing.push(url.clone())
+                            state.missing.push(url.url.clone())
                         }
                     }
                     DepKind::Property(ref keys) => {
@@ -49,6 +69,7 @@ impl super::Validator for Dependencies {
                             if object.get(key).is_none() {
                                 state.errors.push(Box::new(errors::Required {
                                     path: [path, key.as_ref()].join("/"),
+                                    location: Default::default(),
                                 }))
                             }
                         }
@@ -0,0 +1,224 @@
+//! Selection of the branches of `anyOf` and `oneOf` by the value of a property.
+
+use serde_json::Value;
+
+use super::super::errors;
+use super::super::helpers::is_matching;
+use super::super::schema::{Schema, SchemaRef};
+use super::super::scope::Scope;
+
+/// How many `$ref` are followed from a branch to find the value it requires for the
+/// discriminating property.
+const MAX_REFERENCES: usize = 8;
+
+/// An OpenAPI `discriminator` next to `anyOf` or `oneOf`: the property whose value
+/// selects the branch to validate.
+#[derive(Debug)]
+pub struct Discriminator {
+    pub property_name: String,
+    /// The schemas for values of the property given by `mapping`. Other values select
+    /// the branches that require them through `const` or `enum`, or whose `$ref` ends
+    /// with the value, e.g. `#/components/schemas/Cat` for `Cat`.
+    pub mapping: Vec<(String, SchemaRef)>,
+}
+
+/// The branches of an applicator that have to be validated.
+pub(crate) enum Selection<'a> {
+    All,
+    Branches(Vec<&'a SchemaRef>),
+    Unknown(errors::UnknownDiscriminator),
+}
+
+/// What selects the branches of an applicator, derived from the branches once they are
+/// linked so that validation only has to look up the value of the property.
+#[derive(Debug)]
+pub struct Selector {
+    property: String,
+    /// The values that select each branch, in the order of the branches.
+    branches: Vec<Vec<Value>>,
+    /// All values that select a branch, including those of the `mapping`.
+    allowed: Vec<Value>,
+}
+
+impl Selector {
+    /// Derives the selector of the branches `schemes`.
+    ///
+    /// Without a `discriminator` the branches are only narrowed down if
+    /// `Scope::detect_discriminators` is on and every one of them requires a value for
+    /// the same property through `const` or `enum`, in which case the other branches
+    /// cannot match anyway. Returns `None` if the branches are not narrowed down, or if
+    /// one of them cannot be resolved.
+    pub(crate) fn new(
+        schemes: &[SchemaRef],
+        discriminator: Option<&Discriminator>,
+        scope: &Scope,
+    ) -> Option<Selector> {
+        let targets: Vec<&Schema> = schemes
+            .iter()
+            .map(|reference| target(reference, scope))
+            .collect::<Option<_>>()?;
+
+        let property = match discriminator {
+            Some(discriminator) => discriminator.property_name.clone(),
+            None if scope.detect_discriminators => detect(&targets, scope)?,
+            None => return None,
+        };
+
+        let mut allowed: Vec<Value> = discriminator
+            .iter()
+            .flat_map(|discriminator| discriminator.mapping.iter())
+            .map(|(mapped, _)| Value::String(mapped.clone()))
+            .collect();
+        let mut branches = vec![];
+        for target in targets {
+            let values = match pinned(target, &property, scope) {
+                Some(values) => values.into_iter().cloned().collect(),
+                // only an explicit discriminator selects branches by their `$ref`
+                None => reference_name(target)
+                    .map(|name| vec![Value::String(name)])
+                    .unwrap_or_default(),
+            };
+            for value in values.iter() {
+                if !allowed.contains(value) {
+                    allowed.push(value.clone());
+                }
+            }
+            branches.push(values);
+        }
+
+        Some(Selector {
+            property,
+            branches,
+            allowed,
+        })
+    }
+}
+
+/// Selects the branches that can match `val`. Values without the discriminating
+/// property are validated against all branches.
+pub(crate) fn select<'a>(
+    schemes: &'a [SchemaRef],
+    discriminator: Option<&'a Discriminator>,
+    selector: Option<&Selector>,
+    val: &Value,
+    path: &str,
+) -> Selection<'a> {
+    let object = match val.as_object() {
+        Some(object) => object,
+        None => return Selection::All,
+    };
+
+    if let Some(discriminator) = discriminator {
+        let mapped = object
+            .get(&discriminator.property_name)
+            .and_then(Value::as_str)
+            .and_then(|value| {
+                discriminator
+                    .mapping
+                    .iter()
+                    .find(|(mapped, _)| mapped == value)
+            });
+        if let Some((_, reference)) = mapped {
+            return Selection::Branches(vec![reference]);
+        }
+    }
+
+    // the branches that cannot be resolved are reported by the applicator
+    let selector = match selector {
+        Some(selector) => selector,
+        None => return Selection::All,
+    };
+    let value = match object.get(&selector.property) {
+        Some(value) => value,
+        None => return Selection::All,
+    };
+
+    let selected: Vec<&SchemaRef> = schemes
+        .iter()
+        .zip(selector.branches.iter())
+        .filter(|(_, values)| values.iter().any(|pinned| is_matching(pinned, value)))
+        .map(|(reference, _)| reference)
+        .collect();
+
+    if selected.is_empty() {
+        Selection::Unknown(errors::UnknownDiscriminator {
+            path: [path, selector.property.as_str()].join("/"),
+            location: Default::default(),
+            property: selector.property.clone(),
+            allowed: selector.allowed.clone(),
+        })
+    } else {
+        Selection::Branches(selected)
+    }
+}
+
+/// Finds a property for which every branch requires certain values.
+fn detect(branches: &[&Schema], scope: &Scope) -> Option<String> {
+    let names = follow(branches.first()?, scope, |schema| {
+        schema.original.get("properties").and_then(Value::as_object)
+    })?;
+    names
+        .keys()
+        .find(|name| {
+            branches
+                .iter()
+                .all(|branch| pinned(branch, name, scope).is_some())
+        })
+        .cloned()
+}
+
+/// The values a branch requires for the property `name`.
+fn pinned<'s>(schema: &'s Schema, name: &str, scope: &'s Scope) -> Option<Vec<&'s Value>> {
+    follow(schema, scope, |schema| schema.pinned_values(name))
+}
+
+/// Applies `f` to a schema and to the targets of its `$ref` in turn, until it returns
+/// something.
+fn follow<'s, T>(
+    schema: &'s Schema,
+    scope: &'s Scope,
+    f: impl Fn(&'s Schema) -> Option<T>,
+) -> Option<T> {
+    let mut schema = schema;
+    for _ in 0..MAX_REFERENCES {
+        if let Some(found) = f(schema) {
+            return Some(found);
+        }
+        if !schema.has_keyword("$ref") {
+            return None;
+        }
+        let url = schema
+            .original
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|reference| schema.resource.join(reference).ok())?;
+        schema = scope.find(&url)?.1;
+    }
+    None
+}
+
+fn target<'s>(reference: &SchemaRef, scope: &'s Scope) -> Option<&'s Schema> {
+    match reference.link {
+        Some(ref link) => scope.get_linked(link),
+        None => scope.find(&reference.url).map(|(_, schema)| schema),
+    }
+}
+
+/// The name of the schema a branch refers to, i.e. the last segment of the JSON pointer
+/// or of the path (without `.json`) of its `$ref`.
+fn reference_name(branch: &Schema) -> Option<String> {
+    if !branch.has_keyword("$ref") {
+        return None;
+    }
+    let url = branch
+        .original
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| branch.resource.join(reference).ok())?;
+    let name = match url.fragment() {
+        Some(fragment) if !fragment.is_empty() => fragment.rsplit('/').next()?,
+        _ => url.path_segments()?.next_back()?.trim_end_matches(".json"),
+    };
+    let name = percent_encoding::percent_decode_str(name).decode_utf8_lossy();
+    Some(name.replace("~1", "/").replace("~0", "~")).filter(|name| !name.is_empty())
+}
@@ -2,7 +2,7 @@ use serde_json::Value;
 
 use super::super::errors;
 use super::super::helpers::is_matching;
-use super::super::scope;
+use super::super::schema;
 
 #[allow(missing_copy_implementations)]
 pub struct Enum {
@@ -14,7 +14,7 @@ impl super::Validator for Enum {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let mut state = super::ValidationState::new();
@@ -30,6 +30,8 @@ impl super::Validator for Enum {
         if !contains {
             state.errors.push(Box::new(errors::Enum {
                 path: path.to_string(),
+                location: Default::default(),
+                allowed: self.items.clone(),
             }))
         }
 
@@ -9,7 +9,7 @@ use url;
 use uuid;
 
 use super::super::errors;
-use super::super::scope;
+use super::super::schema;
 
 #[allow(missing_copy_implementations)]
 pub struct Date;
@@ -19,7 +19,7 @@ impl super::Validator for Date {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let string = nonstrict_process!(val.as_str(), path);
@@ -31,13 +31,15 @@ impl super::Validator for Date {
                 } else {
                     val_error!(errors::Format {
                         path: path.to_string(),
-                        detail: "Malformed Date".to_string()
+                        detail: "Malformed Date".to_string(),
+                        location: Default::default(),
                     })
                 }
             }
             Err(_) => val_error!(errors::Format {
                 path: path.to_string(),
-                detail: "Malformed date".to_string()
+                detail: "Malformed date".to_string(),
+                location: Default::default(),
             }),
         }
     }
@@ -51,7 +53,7 @@ impl super::Validator for DateTime {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let string = nonstrict_process!(val.as_str(), path);
@@ -60,7 +62,8 @@ impl super::Validator for DateTime {
             Ok(_) => super::ValidationState::new(),
             Err(_) => val_error!(errors::Format {
                 path: path.to_string(),
-                detail: "Malformed date time".to_string()
+                detail: "Malformed date time".to_string(),
+                location: Default::default(),
             }),
         }
     }
@@ -74,7 +77,7 @@ impl super::Validator for Email {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let string = nonstrict_process!(val.as_str(), path);
@@ -83,7 +86,8 @@ impl super::Validator for Email {
             Ok(_) => super::ValidationState::new(),
             Err(_) => val_error!(errors::Format {
                 path: path.to_string(),
-                detail: "Malformed email address".to_string()
+                detail: "Malformed email address".to_string(),
+                location: Default::default(),
             }),
         }
     }
@@ -97,7 +101,7 @@ impl super::Validator for Hostname {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let string = nonstrict_process!(val.as_str(), path);
@@ -106,7 +110,8 @@ impl super::Validator for Hostname {
             Ok(_) => super::ValidationState::new(),
             Err(_) => val_error!(errors::Format {
                 path: path.to_string(),
-                detail: "Malformed hostname".to_string()
+                detail: "Malformed hostname".to_string(),
+                location: Default::default(),
             }),
         }
     }
@@ -120,7 +125,7 @@ impl super::Validator for Ipv4 {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let string = nonstrict_process!(val.as_str(), path);
@@ -129,7 +134,8 @@ impl super::Validator for Ipv4 {
             Ok(_) => super::ValidationState::new(),
             Err(_) => val_error!(errors::Format {
                 path: path.to_string(),
-                detail: "Malformed IP address".to_string()
+                detail: "Malformed IP address".to_string(),
+                location: Default::default(),
             }),
         }
     }
@@ -143,7 +149,7 @@ impl super::Validator for Ipv6 {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let string = nonstrict_process!(val.as_str(), path);
@@ -152,7 +158,8 @@ impl super::Validator for Ipv6 {
             Ok(_) => super::ValidationState::new(),
             Err(_) => val_error!(errors::Format {
                 path: path.to_string(),
-                detail: "Malformed IP address".to_string()
+                detail: "Malformed IP address".to_string(),
+                location: Default::default(),
             }),
         }
     }
@@ -166,7 +173,7 @@ impl super::Validator for IRI {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let string = nonstrict_process!(val.as_str(), path);
@@ -175,7 +182,8 @@ impl super::Validator for IRI {
             Ok(_) => super::ValidationState::new(),
             Err(err) => val_error!(errors::Format {
                 path: path.to_string(),
-                detail: format!("Malformed IRI: {err}")
+                detail: format!("Malformed IRI: {err}"),
+                location: Default::default(),
             }),
         }
     }
@@ -189,7 +197,7 @@ impl super::Validator for IRIReference {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let string = nonstrict_process!(val.as_str(), path);
@@ -200,7 +208,8 @@ impl super::Validator for IRIReference {
             Ok(_) => super::ValidationState::new(),
             Err(err) => val_error!(errors::Format {
                 path: path.to_string(),
-                detail: format!("Malformed IRI reference: {err}")
+                detail: format!("Malformed IRI reference: {err}"),
+                location: Default::default(),
             }),
         }
     }
@@ -214,7 +223,7 @@ impl super::Validator for JsonPointer {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let string = nonstrict_process!(val.as_str(), path);
@@ -223,7 +232,8 @@ impl super::Validator for JsonPointer {
             Ok(_) => super::ValidationState::new(),
             Err(_) => val_error!(errors::Format {
                 path: path.to_string(),
-                detail: "Malformed JSON pointer".to_string()
+                detail: "Malformed JSON pointer".to_string(),
+                location: Default::default(),
             }),
         }
     }
@@ -237,7 +247,7 @@ impl super::Validator for Regex {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let string = nonstrict_process!(val.as_str(), path);
@@ -253,7 +263,8 @@ impl super::Validator for Regex {
             Err(er) => {
                 val_error!(errors::Format {
                     path: path.to_string(),
-                    detail: format!("Malformed regex - {er}")
+                    detail: format!("Malformed regex - {er}"),
+                    location: Default::default(),
                 })
             }
         }
@@ -268,7 +279,7 @@ impl super::Validator for RelativeJsonPointer {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let string = nonstrict_process!(val.as_str(), path);
@@ -277,7 +288,8 @@ impl super::Validator for RelativeJsonPointer {
             Ok(_) => super::ValidationState::new(),
             Err(_) => val_error!(errors::Format {
                 path: path.to_string(),
-                detail: "Malformed relative JSON pointer".to_string()
+                detail: "Malformed relative JSON pointer".to_string(),
+                location: Default::default(),
             }),
         }
     }
@@ -291,7 +303,7 @@ impl super::Validator for Time {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let string = nonstrict_process!(val.as_str(), path);
@@ -300,7 +312,8 @@ impl super::Validator for Time {
             Ok(_) => super::ValidationState::new(),
             Err(_) => val_error!(errors::Format {
                 path: path.to_string(),
-                detail: "Malformed time".to_string()
+                detail: "Malformed time".to_string(),
+                location: Default::default(),
             }),
         }
     }
@@ -314,7 +327,7 @@ impl super::Validator for Uuid {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let string = nonstrict_process!(val.as_str(), path);
@@ -323,7 +336,8 @@ impl super::Validator for Uuid {
             Ok(_) => super::ValidationState::new(),
             Err(err) => val_error!(errors::Format {
                 path: path.to_string(),
-                detail: format!("Malformed UUID: {err:?}")
+                detail: format!("Malformed UUID: {err:?}"),
+                location: Default::default(),
             }),
         }
     }
@@ -337,7 +351,7 @@ impl super::Validator for Uri {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let string = nonstrict_process!(val.as_str(), path);
@@ -346,7 +360,8 @@ impl super::Validator for Uri {
             Ok(_) => super::ValidationState::new(),
             Err(err) => val_error!(errors::Format {
                 path: path.to_string(),
-                detail: format!("Malformed URI: {err}")
+                detail: format!("Malformed URI: {err}"),
+                location: Default::default(),
             }),
         }
     }
@@ -360,7 +375,7 @@ impl super::Validator for UriReference {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let string = nonstrict_process!(val.as_str(), path);
@@ -371,7 +386,8 @@ impl super::Validator for UriReference {
             Ok(_) => super::ValidationState::new(),
             Err(err) => val_error!(errors::Format {
                 path: path.to_string(),
-                detail: format!("Malformed URI reference: {err}")
+                detail: format!("Malformed URI reference: {err}"),
+                location: Default::default(),
             }),
         }
     }
@@ -385,7 +401,7 @@ impl super::Validator for UriTemplate {
         &self,
         val: &Value,
         _path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let string = nonstrict_process!(val.as_str(), path);
@@ -398,6 +414,7 @@ impl super::Validator for UriTemplate {
 #[cfg(test)]
 pub mod tests {
     use super::Regex;
+    use crate::json_schema::schema::ValidationContext;
     use crate::json_schema::validators::Validator;
     use crate::json_schema::{Scope, ValidationState};
 
@@ -424,7 +441,7 @@ pub mod tests {
         let result = validate_regex("FOO\\");
         assert_eq!(result.errors.len(), 1);
 
-        let only_err = result.errors.get(0);
+        let only_err = result.errors.first();
         assert!(only_err.is_some());
 
         let err = only_err.unwrap();
@@ -435,6 +452,11 @@ pub mod tests {
     fn validate_regex(json_string: &str) -> ValidationState {
         let value = serde_json::value::Value::String(json_string.into());
         let scope = Scope::new();
-        Regex {}.validate(&value, "/", &scope, &Default::default())
+        Regex {}.validate(
+            &value,
+            "/",
+            &ValidationContext::new(&scope),
+            &Default::default(),
+        )
     }
 }
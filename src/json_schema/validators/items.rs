@@ -3,43 +3,71 @@ use std::borrow::Cow;
 use std::cmp;
 
 use super::super::errors;
-use super::super::scope;
+use super::super::schema;
 
 #[derive(Debug)]
 pub enum ItemsKind {
-    Schema(url::Url),
-    Array(Vec<url::Url>),
+    Schema(schema::SchemaRef),
+    Array(Vec<schema::SchemaRef>),
 }
 
 #[derive(Debug)]
 pub enum AdditionalKind {
     Boolean(bool),
-    Schema(url::Url),
+    Schema(schema::SchemaRef),
 }
 
 #[allow(missing_copy_implementations)]
 pub struct Items {
     pub items: Option<ItemsKind>,
     pub additional: Option<AdditionalKind>,
+    /// The keyword that limits the additional items.
+    pub additional_location: schema::SchemaLocation,
 }
 
 impl super::Validator for Items {
+    fn references(&self) -> Vec<&schema::SchemaRef> {
+        let mut urls = vec![];
+        match self.items {
+            Some(ItemsKind::Schema(ref url)) => urls.push(url),
+            Some(ItemsKind::Array(ref items)) => urls.extend(items.iter()),
+            None => (),
+        }
+        if let Some(AdditionalKind::Schema(ref url)) = self.additional {
+            urls.push(url);
+        }
+        urls
+    }
+
+    fn references_mut(&mut self) -> Vec<&mut schema::SchemaRef> {
+        let mut urls = vec![];
+        match self.items {
+            Some(ItemsKind::Schema(ref mut url)) => urls.push(url),
+            Some(ItemsKind::Array(ref mut items)) => urls.extend(items.iter_mut()),
+            None => (),
+        }
+        if let Some(AdditionalKind::Schema(ref mut url)) = self.additional {
+            urls.push(url);
+        }
+        urls
+    }
+
     fn validate(
         &self,
         val: &Value,
         path: &str,
-        scope: &scope::Scope,
+        ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let mut array = Cow::Borrowed(nonstrict_process!(val.as_array(), path));
         let mut state = super::ValidationState::new();
 
-        if scope.supply_defaults {
+        if ctx.scope.supply_defaults {
             if let Some(ItemsKind::Array(urls)) = self.items.as_ref() {
                 // supply default values as long as there are more schema default values
                 // than values in the validated array (but stop at first gap)
                 for url in urls.iter().skip(array.len()) {
-                    if let Some(schema) = scope.resolve(url) {
+                    if let Some(schema) = ctx.resolve_ref(url) {
                         if let Some(default) = schema.get_default() {
                             array.to_mut().push(default);
                         } else {
@@ -56,7 +84,7 @@ impl super::Validator for Items {
             Some(ItemsKind::Schema(ref url)) => {
                 // Just validate all items against the schema
 
-                let schema = scope.resolve(url);
+                let schema = ctx.resolve_ref(url);
                 if let Some(schema) = schema {
                     for idx in 0..array.len() {
                         let item = &array[idx];
@@ -69,9 +97,12 @@ impl super::Validator for Items {
                             }
                         }
                         state.append(result);
+                        if ctx.should_stop(&state) {
+                            break;
+                        }
                     }
                 } else {
-                    state.missing.push(url.clone());
+                    state.missing.push(url.url.clone());
                 }
             }
             Some(ItemsKind::Array(ref urls)) => {
@@ -79,7 +110,7 @@ impl super::Validator for Items {
 
                 // Validate against schemas
                 for idx in 0..min {
-                    let schema = scope.resolve(&urls[idx]);
+                    let schema = ctx.resolve_ref(&urls[idx]);
                     let item = &array[idx];
 
                     if let Some(schema) = schema {
@@ -93,8 +124,11 @@ impl super::Validator for Items {
                             }
                         }
                         state.append(result);
+                        if ctx.should_stop(&state) {
+                            break;
+                        }
                     } else {
-                        state.missing.push(urls[idx].clone())
+                        state.missing.push(urls[idx].url.clone())
                     }
                 }
 
@@ -106,6 +140,7 @@ impl super::Validator for Items {
                                 state.errors.push(Box::new(errors::Items {
                                     path: path.to_string(),
                                     detail: "Additional items are not allowed".to_string(),
+                                    location: self.additional_location.error_location(),
                                 }))
                             } else {
                                 for idx in urls.len()..array.len() {
@@ -116,7 +151,7 @@ impl super::Validator for Items {
                             }
                         }
                         Some(AdditionalKind::Schema(ref url)) => {
-                            let schema = scope.resolve(url);
+                            let schema = ctx.resolve_ref(url);
                             if let Some(schema) = schema {
                                 for idx in urls.len()..array.len() {
                                     let item = &array[idx];
@@ -130,9 +165,12 @@ impl super::Validator for Items {
                                         }
                                     }
                                     state.append(result);
+                                    if ctx.should_stop(&state) {
+                                        break;
+                                    }
                                 }
                             } else {
-                                state.missing.push(url.clone())
+                                state.missing.push(url.url.clone())
                             }
                         }
                         _ => (),
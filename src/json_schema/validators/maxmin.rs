@@ -1,11 +1,11 @@
 use serde_json::Value;
 
 use super::super::errors;
-use super::super::scope;
+use super::super::schema;
 
 #[allow(missing_copy_implementations)]
 pub struct Maximum {
-    pub number: f64,
+    pub number: serde_json::Number,
 }
 
 impl super::Validator for Maximum {
@@ -13,16 +13,20 @@ impl super::Validator for Maximum {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
-        let number = nonstrict_process!(val.as_f64(), path);
+        let number = nonstrict_process!(val.as_number(), path);
 
-        if number <= self.number {
+        if number.as_f64().unwrap() <= self.number.as_f64().unwrap() {
             super::ValidationState::new()
         } else {
             val_error!(errors::Maximum {
-                path: path.to_string()
+                path: path.to_string(),
+                location: Default::default(),
+                limit: self.number.clone(),
+                exclusive: false,
+                actual: number.clone(),
             })
         }
     }
@@ -30,7 +34,7 @@ impl super::Validator for Maximum {
 
 #[allow(missing_copy_implementations)]
 pub struct ExclusiveMaximum {
-    pub number: f64,
+    pub number: serde_json::Number,
 }
 
 impl super::Validator for ExclusiveMaximum {
@@ -38,16 +42,20 @@ impl super::Validator for ExclusiveMaximum {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
-        let number = nonstrict_process!(val.as_f64(), path);
+        let number = nonstrict_process!(val.as_number(), path);
 
-        if number < self.number {
+        if number.as_f64().unwrap() < self.number.as_f64().unwrap() {
             super::ValidationState::new()
         } else {
             val_error!(errors::Maximum {
-                path: path.to_string()
+                path: path.to_string(),
+                location: Default::default(),
+                limit: self.number.clone(),
+                exclusive: true,
+                actual: number.clone(),
             })
         }
     }
@@ -55,7 +63,7 @@ impl super::Validator for ExclusiveMaximum {
 
 #[allow(missing_copy_implementations)]
 pub struct Minimum {
-    pub number: f64,
+    pub number: serde_json::Number,
 }
 
 impl super::Validator for Minimum {
@@ -63,16 +71,20 @@ impl super::Validator for Minimum {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
-        let number = nonstrict_process!(val.as_f64(), path);
+        let number = nonstrict_process!(val.as_number(), path);
 
-        if number >= self.number {
+        if number.as_f64().unwrap() >= self.number.as_f64().unwrap() {
             super::ValidationState::new()
         } else {
             val_error!(errors::Minimum {
-                path: path.to_string()
+                path: path.to_string(),
+                location: Default::default(),
+                limit: self.number.clone(),
+                exclusive: false,
+                actual: number.clone(),
             })
         }
     }
@@ -80,7 +92,7 @@ impl super::Validator for Minimum {
 
 #[allow(missing_copy_implementations)]
 pub struct ExclusiveMinimum {
-    pub number: f64,
+    pub number: serde_json::Number,
 }
 
 impl super::Validator for ExclusiveMinimum {
@@ -88,16 +100,20 @@ impl super::Validator for ExclusiveMinimum {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
-        let number = nonstrict_process!(val.as_f64(), path);
+        let number = nonstrict_process!(val.as_number(), path);
 
-        if number > self.number {
+        if number.as_f64().unwrap() > self.number.as_f64().unwrap() {
             super::ValidationState::new()
         } else {
             val_error!(errors::Minimum {
-                path: path.to_string()
+                path: path.to_string(),
+                location: Default::default(),
+                limit: self.number.clone(),
+                exclusive: true,
+                actual: number.clone(),
             })
         }
     }
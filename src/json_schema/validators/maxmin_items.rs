@@ -1,7 +1,7 @@
 use serde_json::Value;
 
 use super::super::errors;
-use super::super::scope;
+use super::super::schema;
 
 #[allow(missing_copy_implementations)]
 pub struct MaxItems {
@@ -13,7 +13,7 @@ impl super::Validator for MaxItems {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let array = nonstrict_process!(val.as_array(), path);
@@ -22,7 +22,10 @@ impl super::Validator for MaxItems {
             super::ValidationState::new()
         } else {
             val_error!(errors::MaxItems {
-                path: path.to_string()
+                path: path.to_string(),
+                location: Default::default(),
+                limit: self.length,
+                actual: array.len() as u64,
             })
         }
     }
@@ -38,7 +41,7 @@ impl super::Validator for MinItems {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let array = nonstrict_process!(val.as_array(), path);
@@ -47,7 +50,10 @@ impl super::Validator for MinItems {
             super::ValidationState::new()
         } else {
             val_error!(errors::MinItems {
-                path: path.to_string()
+                path: path.to_string(),
+                location: Default::default(),
+                limit: self.length,
+                actual: array.len() as u64,
             })
         }
     }
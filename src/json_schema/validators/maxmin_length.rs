@@ -1,7 +1,7 @@
 use serde_json::Value;
 
 use super::super::errors;
-use super::super::scope;
+use super::super::schema;
 
 #[allow(missing_copy_implementations)]
 pub struct MaxLength {
@@ -13,7 +13,7 @@ impl super::Validator for MaxLength {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let string = nonstrict_process!(val.as_str(), path);
@@ -22,7 +22,10 @@ impl super::Validator for MaxLength {
             super::ValidationState::new()
         } else {
             val_error!(errors::MaxLength {
-                path: path.to_string()
+                path: path.to_string(),
+                location: Default::default(),
+                limit: self.length,
+                actual: string.chars().count() as u64,
             })
         }
     }
@@ -38,7 +41,7 @@ impl super::Validator for MinLength {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let string = nonstrict_process!(val.as_str(), path);
@@ -47,7 +50,10 @@ impl super::Validator for MinLength {
             super::ValidationState::new()
         } else {
             val_error!(errors::MinLength {
-                path: path.to_string()
+                path: path.to_string(),
+                location: Default::default(),
+                limit: self.length,
+                actual: string.chars().count() as u64,
             })
         }
     }
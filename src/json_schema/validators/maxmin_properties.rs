@@ -1,7 +1,7 @@
 use serde_json::Value;
 
 use super::super::errors;
-use super::super::scope;
+use super::super::schema;
 
 #[allow(missing_copy_implementations)]
 pub struct MaxProperties {
@@ -13,7 +13,7 @@ impl super::Validator for MaxProperties {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let object = nonstrict_process!(val.as_object(), path);
@@ -22,7 +22,10 @@ impl super::Validator for MaxProperties {
             super::ValidationState::new()
         } else {
             val_error!(errors::MaxProperties {
-                path: path.to_string()
+                path: path.to_string(),
+                location: Default::default(),
+                limit: self.length,
+                actual: object.len() as u64,
             })
         }
     }
@@ -38,7 +41,7 @@ impl super::Validator for MinProperties {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let object = nonstrict_process!(val.as_object(), path);
@@ -47,7 +50,10 @@ impl super::Validator for MinProperties {
             super::ValidationState::new()
         } else {
             val_error!(errors::MinProperties {
-                path: path.to_string()
+                path: path.to_string(),
+                location: Default::default(),
+                limit: self.length,
+                actual: object.len() as u64,
             })
         }
     }
@@ -1,9 +1,11 @@
 use serde::{Serialize, Serializer};
 use serde_json::{to_value, Value};
 use std::borrow::Cow;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt;
 
+use super::super::common::error::ValicoError;
+use super::schema;
 use super::scope;
 
 #[macro_export]
@@ -13,7 +15,10 @@ macro_rules! strict_process {
         if maybe_val.is_none() {
             return val_error!($crate::json_schema::errors::WrongType {
                 path: $path.to_string(),
-                detail: $err.to_string()
+                detail: $err.to_string(),
+                expected: vec![],
+                actual: None,
+                location: Default::default(),
             });
         }
 
@@ -39,6 +44,7 @@ macro_rules! val_error {
             missing: vec![],
             replacement: None,
             evaluated: Default::default(),
+            annotations: Default::default(),
         }
     };
 }
@@ -48,6 +54,7 @@ pub use self::const_::Const;
 pub use self::contains::Contains;
 pub use self::content_media::ContentMedia;
 pub use self::dependencies::Dependencies;
+pub use self::discriminator::{Discriminator, Selector};
 pub use self::enum_::Enum;
 pub use self::items::Items;
 pub use self::maxmin::{ExclusiveMaximum, ExclusiveMinimum, Maximum, Minimum};
@@ -60,7 +67,7 @@ pub use self::of::{AllOf, AnyOf, OneOf};
 pub use self::pattern::Pattern;
 pub use self::properties::Properties;
 pub use self::property_names::PropertyNames;
-pub use self::ref_::Ref;
+pub use self::ref_::{DynamicRef, RecursiveRef, Ref};
 pub use self::required::Required;
 pub use self::type_::Type;
 pub use self::unevaluated::Unevaluated;
@@ -71,6 +78,7 @@ mod const_;
 mod contains;
 pub mod content_media;
 pub mod dependencies;
+mod discriminator;
 mod enum_;
 pub mod formats;
 pub mod items;
@@ -98,6 +106,19 @@ pub struct ValidationState {
     /// Set of paths that have been evaluated so far. Once a path has been evaluated, it should be added
     /// here so that `unevaluatedItems` and `unevaluatedProperties` work.
     pub evaluated: HashSet<String>,
+    pub(crate) annotations: BTreeMap<String, Vec<Annotation>>,
+}
+
+/// The value of an annotation keyword (such as `title` or `default`) of a schema that
+/// validated an instance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub keyword: String,
+    pub value: Value,
+    /// The evaluation path of the keyword, see `ValicoError::get_keyword_location`.
+    pub keyword_location: String,
+    /// The absolute URL of the keyword.
+    pub schema_url: String,
 }
 
 impl ValidationState {
@@ -107,6 +128,7 @@ impl ValidationState {
             missing: vec![],
             replacement: None,
             evaluated: Default::default(),
+            annotations: Default::default(),
         }
     }
 
@@ -114,14 +136,42 @@ impl ValidationState {
         self.errors.is_empty()
     }
 
+    /// The annotations of the schemas that validated successfully, by instance path. They
+    /// are only collected if the scope was set up with `Scope::collect_annotations`.
+    pub fn annotations(&self) -> &BTreeMap<String, Vec<Annotation>> {
+        &self.annotations
+    }
+
     pub fn is_strictly_valid(&self) -> bool {
         self.errors.is_empty() && self.missing.is_empty()
     }
 
+    /// The error that most likely explains why the value is invalid, e.g. to show only
+    /// one error to a user.
+    ///
+    /// This is the error with the deepest instance path. A failed `anyOf` or `oneOf` is
+    /// replaced by the best error of its most relevant branch: preferably one meant for
+    /// the value, i.e. its `type` holds for the value and its `const` and `enum` for the
+    /// value and its properties, then the one that got deepest into the value,
+    /// then the one with the fewest errors.
+    pub fn best_error(&self) -> Option<&dyn ValicoError> {
+        super::best_match::best_error(&self.errors)
+    }
+
     pub fn append(&mut self, second: ValidationState) {
         self.errors.extend(second.errors);
         self.missing.extend(second.missing);
         self.evaluated.extend(second.evaluated);
+        self.append_annotations(second.annotations);
+    }
+
+    pub fn append_annotations(&mut self, annotations: BTreeMap<String, Vec<Annotation>>) {
+        for (path, annotations) in annotations {
+            self.annotations
+                .entry(path)
+                .or_default()
+                .extend(annotations);
+        }
     }
 
     pub fn set_replacement<T: Clone + Into<Value>>(&mut self, data: Cow<T>) {
@@ -158,6 +208,30 @@ impl Serialize for ValidationState {
                     .collect::<Vec<Value>>(),
             ),
         );
+        if !self.annotations.is_empty() {
+            map.insert(
+                "annotations".to_string(),
+                Value::Object(
+                    self.annotations
+                        .iter()
+                        .map(|(path, annotations)| {
+                            let annotations = annotations
+                                .iter()
+                                .map(|annotation| {
+                                    json!({
+                                        "keyword": annotation.keyword,
+                                        "value": annotation.value,
+                                        "keywordLocation": annotation.keyword_location,
+                                        "absoluteKeywordLocation": annotation.schema_url,
+                                    })
+                                })
+                                .collect();
+                            (path.clone(), Value::Array(annotations))
+                        })
+                        .collect(),
+                ),
+            );
+        }
         Value::Object(map).serialize(serializer)
     }
 }
@@ -167,9 +241,29 @@ pub trait Validator {
         &self,
         item: &Value,
         _: &str,
-        _: &scope::Scope,
+        _: &schema::ValidationContext<'_>,
         prev_state: &ValidationState,
     ) -> ValidationState;
+
+    /// The schemas this validator delegates to.
+    fn references(&self) -> Vec<&schema::SchemaRef> {
+        vec![]
+    }
+
+    /// The same as `references`, for linking them once all schemas are compiled.
+    fn references_mut(&mut self) -> Vec<&mut schema::SchemaRef> {
+        vec![]
+    }
+
+    /// Derives how an applicator selects its branches from the targets of its references,
+    /// once they are linked.
+    fn selector(&self, _scope: &scope::Scope) -> Option<Selector> {
+        None
+    }
+
+    /// Stores what `selector` derived, which is done again whenever documents are
+    /// removed from the scope.
+    fn link(&mut self, _selector: Option<Selector>) {}
 }
 
 impl fmt::Debug for dyn Validator + 'static + Send + Sync {
@@ -183,15 +277,15 @@ pub type Validators = Vec<BoxedValidator>;
 
 impl<T> Validator for T
 where
-    T: Fn(&Value, &str, &scope::Scope, &super::ValidationState) -> ValidationState,
+    T: Fn(&Value, &str, &schema::ValidationContext<'_>, &super::ValidationState) -> ValidationState,
 {
     fn validate(
         &self,
         val: &Value,
         path: &str,
-        scope: &scope::Scope,
+        ctx: &schema::ValidationContext<'_>,
         state: &super::ValidationState,
     ) -> ValidationState {
-        self(val, path, scope, state)
+        self(val, path, ctx, state)
     }
 }
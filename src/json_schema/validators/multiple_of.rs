@@ -1,13 +1,13 @@
 use serde_json::Value;
 
 use super::super::errors;
-use super::super::scope;
+use super::super::schema;
 use std::cmp::Ordering;
 use std::f64;
 
 #[allow(missing_copy_implementations)]
 pub struct MultipleOf {
-    pub number: f64,
+    pub number: serde_json::Number,
 }
 
 impl super::Validator for MultipleOf {
@@ -15,15 +15,17 @@ impl super::Validator for MultipleOf {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
-        let number = nonstrict_process!(val.as_f64(), path);
+        let actual = nonstrict_process!(val.as_number(), path);
+        let number = actual.as_f64().unwrap();
+        let multiple_of = self.number.as_f64().unwrap();
 
-        let valid = if (number.fract() == 0f64) && (self.number.fract() == 0f64) {
-            (number % self.number) == 0f64
+        let valid = if (number.fract() == 0f64) && (multiple_of.fract() == 0f64) {
+            (number % multiple_of) == 0f64
         } else {
-            let remainder: f64 = (number / self.number) % 1f64;
+            let remainder: f64 = (number / multiple_of) % 1f64;
             let remainder_less_than_epsilon = matches!(
                 remainder.partial_cmp(&f64::EPSILON),
                 None | Some(Ordering::Less)
@@ -36,7 +38,10 @@ impl super::Validator for MultipleOf {
             super::ValidationState::new()
         } else {
             val_error!(errors::MultipleOf {
-                path: path.to_string()
+                path: path.to_string(),
+                location: Default::default(),
+                multiple_of: self.number.clone(),
+                actual: actual.clone(),
             })
         }
     }
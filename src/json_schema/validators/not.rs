@@ -1,32 +1,41 @@
 use serde_json::Value;
 
 use super::super::errors;
-use super::super::scope;
+use super::super::schema;
 
 #[allow(missing_copy_implementations)]
 pub struct Not {
-    pub url: url::Url,
+    pub url: schema::SchemaRef,
 }
 
 impl super::Validator for Not {
+    fn references(&self) -> Vec<&schema::SchemaRef> {
+        vec![&self.url]
+    }
+
+    fn references_mut(&mut self) -> Vec<&mut schema::SchemaRef> {
+        vec![&mut self.url]
+    }
+
     fn validate(
         &self,
         val: &Value,
         path: &str,
-        scope: &scope::Scope,
+        ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
-        let schema = scope.resolve(&self.url);
+        let schema = ctx.resolve_ref(&self.url);
         let mut state = super::ValidationState::new();
 
         if let Some(schema) = schema {
             if schema.validate_in(val, path).is_valid() {
                 state.errors.push(Box::new(errors::Not {
                     path: path.to_string(),
+                    location: Default::default(),
                 }))
             }
         } else {
-            state.missing.push(self.url.clone());
+            state.missing.push(self.url.url.clone());
         }
 
         state
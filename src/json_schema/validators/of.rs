@@ -2,20 +2,31 @@ use serde_json::Value;
 use std::borrow::Cow;
 use std::collections::HashSet;
 
+use super::super::best_match;
 use super::super::errors;
+use super::super::schema;
 use super::super::scope;
+use super::discriminator::{self, Selection, Selector};
 
 #[allow(missing_copy_implementations)]
 pub struct AllOf {
-    pub schemes: Vec<url::Url>,
+    pub schemes: Vec<schema::SchemaRef>,
 }
 
 impl super::Validator for AllOf {
+    fn references(&self) -> Vec<&schema::SchemaRef> {
+        self.schemes.iter().collect()
+    }
+
+    fn references_mut(&mut self) -> Vec<&mut schema::SchemaRef> {
+        self.schemes.iter_mut().collect()
+    }
+
     fn validate(
         &self,
         val: &Value,
         path: &str,
-        scope: &scope::Scope,
+        ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let mut state = super::ValidationState::new();
@@ -25,10 +36,10 @@ impl super::Validator for AllOf {
         let schemas = self
             .schemes
             .iter()
-            .map(|url| (url, scope.resolve(url)))
+            .map(|url| (url, ctx.resolve_ref(url)))
             .filter_map(|(url, opt)| {
                 if opt.is_none() {
-                    state.missing.push(url.clone())
+                    state.missing.push(url.url.clone())
                 }
                 opt
             })
@@ -41,6 +52,9 @@ impl super::Validator for AllOf {
                 *val.to_mut() = result.replacement.take().unwrap();
             }
             state.append(result);
+            if ctx.should_stop(&state) {
+                return state;
+            }
         }
         if !state.is_valid() {
             return state;
@@ -60,6 +74,7 @@ impl super::Validator for AllOf {
             if let Cow::Owned(_) = second {
                 state.errors.push(Box::new(errors::DivergentDefaults {
                     path: path.to_string(),
+                    location: Default::default(),
                 }));
             }
             if !state.is_valid() {
@@ -75,26 +90,69 @@ impl super::Validator for AllOf {
 
 #[allow(missing_copy_implementations)]
 pub struct AnyOf {
-    pub schemes: Vec<url::Url>,
+    pub schemes: Vec<schema::SchemaRef>,
+    pub discriminator: Option<super::Discriminator>,
+    /// Derived from the branches whenever the scope links its documents.
+    pub selector: Option<Selector>,
 }
 
 impl super::Validator for AnyOf {
+    fn references(&self) -> Vec<&schema::SchemaRef> {
+        let mapping = self.discriminator.iter().flat_map(|d| d.mapping.iter());
+        self.schemes
+            .iter()
+            .chain(mapping.map(|(_, reference)| reference))
+            .collect()
+    }
+
+    fn references_mut(&mut self) -> Vec<&mut schema::SchemaRef> {
+        let mapping = self
+            .discriminator
+            .iter_mut()
+            .flat_map(|d| d.mapping.iter_mut());
+        self.schemes
+            .iter_mut()
+            .chain(mapping.map(|(_, reference)| reference))
+            .collect()
+    }
+
+    fn selector(&self, scope: &scope::Scope) -> Option<Selector> {
+        Selector::new(&self.schemes, self.discriminator.as_ref(), scope)
+    }
+
+    fn link(&mut self, selector: Option<Selector>) {
+        self.selector = selector;
+    }
+
     fn validate(
         &self,
         val: &Value,
         path: &str,
-        scope: &scope::Scope,
+        ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let mut state = super::ValidationState::new();
         let mut val = Cow::Borrowed(val);
 
+        let schemes = match discriminator::select(
+            &self.schemes,
+            self.discriminator.as_ref(),
+            self.selector.as_ref(),
+            &val,
+            path,
+        ) {
+            Selection::All => self.schemes.iter().collect(),
+            Selection::Branches(branches) => branches,
+            Selection::Unknown(error) => return val_error!(error),
+        };
+
         let mut invalid_states = vec![];
         // The "best" state is defined as "the one that validates the most items".
         let mut evaluated: HashSet<String> = HashSet::new();
+        let mut annotations = vec![];
         let mut valid = false;
-        for url in self.schemes.iter() {
-            let schema = scope.resolve(url);
+        for url in schemes {
+            let schema = ctx.resolve_ref(url);
 
             if let Some(schema) = schema {
                 let mut result = schema.validate_in(&val, path);
@@ -107,22 +165,30 @@ impl super::Validator for AnyOf {
                     }
                     valid = true;
                     evaluated.extend(result.evaluated);
+                    annotations.push(result.annotations);
                     // Cannot short-circuit here as "unevaluatedItems" requires that we find the "best" state.
                 } else {
                     invalid_states.push(result)
                 }
             } else {
-                state.missing.push(url.clone())
+                state.missing.push(url.url.clone())
             }
         }
 
         if !valid {
+            if ctx.scope.collapse_errors {
+                collapse(&mut invalid_states, path);
+            }
             state.errors.push(Box::new(errors::AnyOf {
                 path: path.to_string(),
                 states: invalid_states,
+                location: Default::default(),
             }))
         } else {
             state.evaluated.extend(evaluated);
+            for annotations in annotations {
+                state.append_annotations(annotations);
+            }
         }
 
         state.set_replacement(val);
@@ -132,25 +198,68 @@ impl super::Validator for AnyOf {
 
 #[allow(missing_copy_implementations)]
 pub struct OneOf {
-    pub schemes: Vec<url::Url>,
+    pub schemes: Vec<schema::SchemaRef>,
+    pub discriminator: Option<super::Discriminator>,
+    /// Derived from the branches whenever the scope links its documents.
+    pub selector: Option<Selector>,
 }
 
 impl super::Validator for OneOf {
+    fn references(&self) -> Vec<&schema::SchemaRef> {
+        let mapping = self.discriminator.iter().flat_map(|d| d.mapping.iter());
+        self.schemes
+            .iter()
+            .chain(mapping.map(|(_, reference)| reference))
+            .collect()
+    }
+
+    fn references_mut(&mut self) -> Vec<&mut schema::SchemaRef> {
+        let mapping = self
+            .discriminator
+            .iter_mut()
+            .flat_map(|d| d.mapping.iter_mut());
+        self.schemes
+            .iter_mut()
+            .chain(mapping.map(|(_, reference)| reference))
+            .collect()
+    }
+
+    fn selector(&self, scope: &scope::Scope) -> Option<Selector> {
+        Selector::new(&self.schemes, self.discriminator.as_ref(), scope)
+    }
+
+    fn link(&mut self, selector: Option<Selector>) {
+        self.selector = selector;
+    }
+
     fn validate(
         &self,
         val: &Value,
         path: &str,
-        scope: &scope::Scope,
+        ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let mut state = super::ValidationState::new();
         let mut val = Cow::Borrowed(val);
 
+        let schemes = match discriminator::select(
+            &self.schemes,
+            self.discriminator.as_ref(),
+            self.selector.as_ref(),
+            &val,
+            path,
+        ) {
+            Selection::All => self.schemes.iter().collect(),
+            Selection::Branches(branches) => branches,
+            Selection::Unknown(error) => return val_error!(error),
+        };
+
         let mut states = vec![];
         let mut valid = 0;
         let mut evaluated = HashSet::new();
-        for url in self.schemes.iter() {
-            let schema = scope.resolve(url);
+        let mut annotations = Default::default();
+        for url in schemes {
+            let schema = ctx.resolve_ref(url);
 
             if let Some(schema) = schema {
                 let mut result = schema.validate_in(&val, path);
@@ -163,24 +272,44 @@ impl super::Validator for OneOf {
                     }
                     valid += 1;
                     evaluated = result.evaluated;
+                    annotations = result.annotations;
+                    if valid > 1 && ctx.has_error_limit() {
+                        // the outcome cannot change anymore
+                        break;
+                    }
                 } else {
                     states.push(result)
                 }
             } else {
-                state.missing.push(url.clone())
+                state.missing.push(url.url.clone())
             }
         }
 
         if valid != 1 {
+            if valid == 0 && ctx.scope.collapse_errors {
+                collapse(&mut states, path);
+            }
             state.errors.push(Box::new(errors::OneOf {
                 path: path.to_string(),
                 states,
+                matched: valid,
+                location: Default::default(),
             }))
         } else {
             state.evaluated = evaluated;
+            state.annotations = annotations;
         }
 
         state.set_replacement(val);
         state
     }
 }
+
+/// Keeps only the most relevant of the failed branches, see
+/// `Scope::collapse_applicator_errors`.
+fn collapse(states: &mut Vec<super::ValidationState>, path: &str) {
+    if let Some(idx) = best_match::best_branch(states, path) {
+        let best = states.swap_remove(idx);
+        *states = vec![best];
+    }
+}
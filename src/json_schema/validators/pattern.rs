@@ -1,7 +1,7 @@
 use serde_json::Value;
 
 use super::super::errors;
-use super::super::scope;
+use super::super::schema;
 
 #[allow(missing_copy_implementations)]
 pub struct Pattern {
@@ -13,7 +13,7 @@ impl super::Validator for Pattern {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let string = nonstrict_process!(val.as_str(), path);
@@ -22,7 +22,8 @@ impl super::Validator for Pattern {
             super::ValidationState::new()
         } else {
             val_error!(errors::Pattern {
-                path: path.to_string()
+                path: path.to_string(),
+                location: Default::default(),
             })
         }
     }
@@ -1,38 +1,60 @@
 use serde_json::Value;
 use std::borrow::Cow;
 use std::collections;
+use std::sync::Arc;
 
 use super::super::errors;
-use super::super::scope;
+use super::super::schema;
 
 #[derive(Debug)]
 pub enum AdditionalKind {
     Unspecified,
     Boolean(bool),
-    Schema(url::Url),
+    Schema(schema::SchemaRef),
 }
 
 #[allow(missing_copy_implementations)]
 pub struct Properties {
-    pub properties: collections::HashMap<String, url::Url>,
+    pub properties: collections::HashMap<String, schema::SchemaRef>,
+    /// The names of `properties`, sorted, for the errors of `additionalProperties`.
+    pub known: Arc<[String]>,
     pub additional: AdditionalKind,
-    pub patterns: Vec<(fancy_regex::Regex, url::Url)>,
+    pub additional_location: schema::SchemaLocation,
+    pub patterns: Vec<(fancy_regex::Regex, schema::SchemaRef)>,
 }
 
 impl super::Validator for Properties {
+    fn references(&self) -> Vec<&schema::SchemaRef> {
+        let mut urls: Vec<&schema::SchemaRef> = self.properties.values().collect();
+        urls.extend(self.patterns.iter().map(|(_, url)| url));
+        if let AdditionalKind::Schema(ref url) = self.additional {
+            urls.push(url);
+        }
+        urls
+    }
+
+    fn references_mut(&mut self) -> Vec<&mut schema::SchemaRef> {
+        let mut urls: Vec<&mut schema::SchemaRef> = self.properties.values_mut().collect();
+        urls.extend(self.patterns.iter_mut().map(|(_, url)| url));
+        if let AdditionalKind::Schema(ref mut url) = self.additional {
+            urls.push(url);
+        }
+        urls
+    }
+
     fn validate(
         &self,
         val: &Value,
         path: &str,
-        scope: &scope::Scope,
+        ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let mut object = Cow::Borrowed(nonstrict_process!(val.as_object(), path));
         let mut state = super::ValidationState::new();
 
-        if scope.supply_defaults {
+        if ctx.scope.supply_defaults {
             for (key, url) in self.properties.iter() {
-                if let Some(schema) = scope.resolve(url) {
+                if let Some(schema) = ctx.resolve_ref(url) {
                     if object.get(key).is_none() && schema.has_default() {
                         object
                             .to_mut()
@@ -45,9 +67,12 @@ impl super::Validator for Properties {
         // necessary due to object being mutated in the loop
         let keys = object.keys().cloned().collect::<Vec<_>>();
         'main: for key in keys.iter() {
+            if ctx.should_stop(&state) {
+                break;
+            }
             let is_property_passed = if self.properties.contains_key(key) {
                 let url = &self.properties[key];
-                let schema = scope.resolve(url);
+                let schema = ctx.resolve_ref(url);
                 if let Some(schema) = schema {
                     let value_path = [path, key.as_ref()].join("/");
                     let mut result = schema.validate_in(&object[key], value_path.as_ref());
@@ -61,7 +86,7 @@ impl super::Validator for Properties {
                     }
                     state.append(result);
                 } else {
-                    state.missing.push(url.clone())
+                    state.missing.push(url.url.clone())
                 }
 
                 true
@@ -72,7 +97,7 @@ impl super::Validator for Properties {
             let mut is_pattern_passed = false;
             for (regex, url) in self.patterns.iter() {
                 if regex.is_match(key.as_ref()).unwrap_or(false) {
-                    let schema = scope.resolve(url);
+                    let schema = ctx.resolve_ref(url);
                     if let Some(schema) = schema {
                         let value_path = [path, key.as_ref()].join("/");
                         let mut result = schema.validate_in(&object[key], value_path.as_ref());
@@ -87,7 +112,7 @@ impl super::Validator for Properties {
                         state.append(result);
                         is_pattern_passed = true;
                     } else {
-                        state.missing.push(url.clone())
+                        state.missing.push(url.url.clone())
                     }
                 }
             }
@@ -102,13 +127,16 @@ impl super::Validator for Properties {
                         state.errors.push(Box::new(errors::Properties {
                             path: path.to_string(),
                             detail: format!("Additional property '{key}' is not allowed"),
+                            location: self.additional_location.error_location(),
+                            property: key.clone(),
+                            known: self.known.clone(),
                         }))
                     } else {
                         state.evaluated.insert([path, key.as_ref()].join("/"));
                     }
                 }
                 AdditionalKind::Schema(ref url) => {
-                    let schema = scope.resolve(url);
+                    let schema = ctx.resolve_ref(url);
 
                     if let Some(schema) = schema {
                         let value_path = [path, key.as_ref()].join("/");
@@ -123,7 +151,7 @@ impl super::Validator for Properties {
                         }
                         state.append(result);
                     } else {
-                        state.missing.push(url.clone())
+                        state.missing.push(url.url.clone())
                     }
                 }
                 // Additional are allowed here
@@ -1,23 +1,31 @@
 use serde_json::Value;
 
-use super::super::scope;
+use super::super::schema;
 
 #[allow(missing_copy_implementations)]
 pub struct PropertyNames {
-    pub url: url::Url,
+    pub url: schema::SchemaRef,
 }
 
 impl super::Validator for PropertyNames {
+    fn references(&self) -> Vec<&schema::SchemaRef> {
+        vec![&self.url]
+    }
+
+    fn references_mut(&mut self) -> Vec<&mut schema::SchemaRef> {
+        vec![&mut self.url]
+    }
+
     fn validate(
         &self,
         val: &Value,
         path: &str,
-        scope: &scope::Scope,
+        ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let object = nonstrict_process!(val.as_object(), path);
 
-        let schema = scope.resolve(&self.url);
+        let schema = ctx.resolve_ref(&self.url);
         let mut state = super::ValidationState::new();
 
         if let Some(schema) = schema {
@@ -26,7 +34,7 @@ impl super::Validator for PropertyNames {
                 state.append(schema.validate_in(&Value::from(key.clone()), item_path.as_ref()));
             }
         } else {
-            state.missing.push(self.url.clone());
+            state.missing.push(self.url.url.clone());
         }
 
         state
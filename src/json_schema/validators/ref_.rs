@@ -1,27 +1,119 @@
 use serde_json::Value;
 
-use super::super::scope;
+use super::super::schema;
 
 #[allow(missing_copy_implementations)]
 pub struct Ref {
-    pub url: url::Url,
+    pub url: schema::SchemaRef,
 }
 
 impl super::Validator for Ref {
+    fn references(&self) -> Vec<&schema::SchemaRef> {
+        vec![&self.url]
+    }
+
+    fn references_mut(&mut self) -> Vec<&mut schema::SchemaRef> {
+        vec![&mut self.url]
+    }
+
+    fn validate(
+        &self,
+        val: &Value,
+        path: &str,
+        ctx: &schema::ValidationContext<'_>,
+        _: &super::ValidationState,
+    ) -> super::ValidationState {
+        let schema = ctx.resolve_ref(&self.url);
+
+        if let Some(schema) = schema {
+            schema.validate_in(val, path)
+        } else {
+            let mut state = super::ValidationState::new();
+            state.missing.push(self.url.url.clone());
+            state
+        }
+    }
+}
+
+#[allow(missing_copy_implementations)]
+pub struct RecursiveRef {
+    pub url: schema::SchemaRef,
+}
+
+impl super::Validator for RecursiveRef {
+    fn references(&self) -> Vec<&schema::SchemaRef> {
+        vec![&self.url]
+    }
+
+    fn references_mut(&mut self) -> Vec<&mut schema::SchemaRef> {
+        vec![&mut self.url]
+    }
+
+    fn validate(
+        &self,
+        val: &Value,
+        path: &str,
+        ctx: &schema::ValidationContext<'_>,
+        _: &super::ValidationState,
+    ) -> super::ValidationState {
+        let schema = ctx.resolve_ref(&self.url).map(|schema| {
+            // the dynamic scope is only consulted if the initially resolved schema sets
+            // $recursiveAnchor, otherwise this behaves like $ref
+            if schema.has_recursive_anchor() {
+                ctx.resolve_recursive_anchor().unwrap_or(schema)
+            } else {
+                schema
+            }
+        });
+
+        if let Some(schema) = schema {
+            schema.validate_in(val, path)
+        } else {
+            let mut state = super::ValidationState::new();
+            state.missing.push(self.url.url.clone());
+            state
+        }
+    }
+}
+
+#[allow(missing_copy_implementations)]
+pub struct DynamicRef {
+    pub url: schema::SchemaRef,
+    pub anchor: Option<String>,
+}
+
+impl super::Validator for DynamicRef {
+    fn references(&self) -> Vec<&schema::SchemaRef> {
+        vec![&self.url]
+    }
+
+    fn references_mut(&mut self) -> Vec<&mut schema::SchemaRef> {
+        vec![&mut self.url]
+    }
+
     fn validate(
         &self,
         val: &Value,
         path: &str,
-        scope: &scope::Scope,
+        ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
-        let schema = scope.resolve(&self.url);
+        let schema = ctx.resolve_ref(&self.url).map(|schema| {
+            // the dynamic scope is only consulted if the initially resolved schema
+            // declares a matching $dynamicAnchor, otherwise this behaves like $ref
+            match self.anchor {
+                Some(ref anchor) if schema.has_dynamic_anchor(anchor) => {
+                    ctx.resolve_dynamic_anchor(anchor).unwrap_or(schema)
+                }
+                _ => schema,
+            }
+        });
 
         if let Some(schema) = schema {
             schema.validate_in(val, path)
         } else {
             let mut state = super::ValidationState::new();
-            state.missing.push(self.url.clone());
+            state.missing.push(self.url.url.clone());
             state
         }
     }
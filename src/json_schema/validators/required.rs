@@ -1,7 +1,7 @@
 use serde_json::Value;
 
 use super::super::errors;
-use super::super::scope;
+use super::super::schema;
 
 #[allow(missing_copy_implementations)]
 pub struct Required {
@@ -13,7 +13,7 @@ impl super::Validator for Required {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let object = nonstrict_process!(val.as_object(), path);
@@ -23,6 +23,7 @@ impl super::Validator for Required {
             if !object.contains_key(key) {
                 state.errors.push(Box::new(errors::Required {
                     path: [path, key.as_ref()].join("/"),
+                    location: Default::default(),
                 }))
             }
         }
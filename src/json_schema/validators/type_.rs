@@ -1,7 +1,7 @@
 use serde_json::Value;
 
 use super::super::errors;
-use super::super::scope;
+use super::super::schema;
 
 use crate::json_schema;
 
@@ -14,15 +14,19 @@ pub enum TypeKind {
 #[allow(missing_copy_implementations)]
 pub struct Type {
     pub item: TypeKind,
+    /// Whether numbers with a zero fractional part, such as `1.0`, are integers. They
+    /// are not in draft 4.
+    pub integral_floats: bool,
 }
 
-fn check_type(val: &Value, ty: json_schema::PrimitiveType) -> bool {
+fn check_type(val: &Value, ty: json_schema::PrimitiveType, integral_floats: bool) -> bool {
     match ty {
         json_schema::PrimitiveType::Array => val.is_array(),
         json_schema::PrimitiveType::Boolean => val.is_boolean(),
         json_schema::PrimitiveType::Integer => {
             let is_true_integer = val.is_u64() || val.is_i64();
-            let is_integer_float = val.is_f64() && val.as_f64().unwrap().fract() == 0.0;
+            let is_integer_float =
+                integral_floats && val.is_f64() && val.as_f64().unwrap().fract() == 0.0;
             is_true_integer || is_integer_float
         }
         json_schema::PrimitiveType::Number => val.is_number(),
@@ -37,17 +41,20 @@ impl super::Validator for Type {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let mut state = super::ValidationState::new();
 
         match self.item {
             TypeKind::Single(t) => {
-                if !check_type(val, t) {
+                if !check_type(val, t, self.integral_floats) {
                     state.errors.push(Box::new(errors::WrongType {
                         path: path.to_string(),
                         detail: format!("The value must be {t}"),
+                        expected: vec![t],
+                        actual: Some(json_schema::PrimitiveType::of(val)),
+                        location: Default::default(),
                     }))
                 } else {
                     state.evaluated.insert(path.to_owned());
@@ -56,7 +63,7 @@ impl super::Validator for Type {
             TypeKind::Set(ref set) => {
                 let mut is_type_match = false;
                 for ty in set.iter() {
-                    if check_type(val, *ty) {
+                    if check_type(val, *ty, self.integral_floats) {
                         is_type_match = true;
                         break;
                     }
@@ -72,6 +79,9 @@ impl super::Validator for Type {
                                 .collect::<Vec<String>>()
                                 .join(", ")
                         ),
+                        expected: set.clone(),
+                        actual: Some(json_schema::PrimitiveType::of(val)),
+                        location: Default::default(),
                     }))
                 } else {
                     state.evaluated.insert(path.to_owned());
@@ -1,10 +1,11 @@
 use std::{borrow::Cow, collections::HashSet};
 
 use crate::json_schema::errors;
+use crate::json_schema::schema;
 
 pub enum UnevaluatedSchema {
     Bool(bool),
-    Schema(url::Url),
+    Schema(schema::SchemaRef),
 }
 
 pub struct Unevaluated {
@@ -17,7 +18,7 @@ impl Unevaluated {
         &self,
         item_path: String,
         item: &serde_json::Value,
-        scope: &crate::json_schema::scope::Scope,
+        ctx: &crate::json_schema::schema::ValidationContext<'_>,
     ) -> super::ValidationState {
         let mut state = super::ValidationState::new();
         match self.schema {
@@ -30,13 +31,14 @@ impl Unevaluated {
                         } else {
                             "Unevaluated properties are not allowed".to_string()
                         },
+                        location: Default::default(),
                     }));
                 } else {
                     state.evaluated.insert(item_path);
                 }
             }
             UnevaluatedSchema::Schema(ref url) => {
-                let schema = scope.resolve(url);
+                let schema = ctx.resolve_ref(url);
                 if let Some(schema) = schema {
                     let mut result = schema.validate_in(item, item_path.as_ref());
                     if result.is_valid() {
@@ -46,7 +48,7 @@ impl Unevaluated {
 
                     state.append(result);
                 } else {
-                    state.missing.push(url.clone())
+                    state.missing.push(url.url.clone())
                 }
             }
         }
@@ -56,11 +58,25 @@ impl Unevaluated {
 }
 
 impl super::Validator for Unevaluated {
+    fn references(&self) -> Vec<&schema::SchemaRef> {
+        match self.schema {
+            UnevaluatedSchema::Schema(ref url) => vec![url],
+            UnevaluatedSchema::Bool(_) => vec![],
+        }
+    }
+
+    fn references_mut(&mut self) -> Vec<&mut schema::SchemaRef> {
+        match self.schema {
+            UnevaluatedSchema::Schema(ref mut url) => vec![url],
+            UnevaluatedSchema::Bool(_) => vec![],
+        }
+    }
+
     fn validate(
         &self,
         val: &serde_json::Value,
         path: &str,
-        scope: &crate::json_schema::scope::Scope,
+        ctx: &crate::json_schema::schema::ValidationContext<'_>,
         state: &super::ValidationState,
     ) -> super::ValidationState {
         let evaluated_children: HashSet<_> = state
@@ -80,7 +96,7 @@ impl super::Validator for Unevaluated {
                     continue;
                 }
 
-                let mut result = self.check_one_item(item_path, item, scope);
+                let mut result = self.check_one_item(item_path, item, ctx);
                 if result.replacement.is_some() {
                     array.to_mut()[idx] = result.replacement.take().unwrap();
                 }
@@ -98,7 +114,7 @@ impl super::Validator for Unevaluated {
                     continue;
                 }
 
-                let mut result = self.check_one_item(item_path, item, scope);
+                let mut result = self.check_one_item(item_path, item, ctx);
                 if result.replacement.is_some() {
                     *item = result.replacement.take().unwrap();
                     changed = true;
@@ -1,7 +1,7 @@
 use serde_json::Value;
 
 use super::super::errors;
-use super::super::scope;
+use super::super::schema;
 
 #[allow(missing_copy_implementations)]
 pub struct UniqueItems;
@@ -10,7 +10,7 @@ impl super::Validator for UniqueItems {
         &self,
         val: &Value,
         path: &str,
-        _scope: &scope::Scope,
+        _ctx: &schema::ValidationContext<'_>,
         _: &super::ValidationState,
     ) -> super::ValidationState {
         let array = nonstrict_process!(val.as_array(), path);
@@ -38,7 +38,8 @@ impl super::Validator for UniqueItems {
             super::ValidationState::new()
         } else {
             val_error!(errors::UniqueItems {
-                path: path.to_string()
+                path: path.to_string(),
+                location: Default::default(),
             })
         }
     }
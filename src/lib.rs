@@ -1,4 +1,8 @@
-#![allow(clippy::bool_assert_comparison, clippy::new_without_default)]
+#![allow(
+    clippy::bool_assert_comparison,
+    clippy::new_without_default,
+    clippy::unnecessary_unwrap
+)]
 
 #[macro_use]
 extern crate serde_json;
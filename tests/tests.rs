@@ -1,3 +1,5 @@
+#![allow(non_fmt_panics)]
+
 #[macro_use]
 extern crate serde_json;
 